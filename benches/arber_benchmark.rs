@@ -34,6 +34,38 @@ fn make_mmr(num_leafs: u8) -> MerkleMountainRange<E, VecStore<E>> {
     mmr
 }
 
+/// Append 1M leaves, optionally reserving capacity upfront, and return the number of
+/// reallocations of the store's `data` and `hashes` vectors, i.e. capacity changes.
+fn count_reallocations(reserve: bool) -> usize {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    if reserve {
+        mmr.reserve(1_000_000);
+    }
+
+    let capacity = |mmr: &MerkleMountainRange<E, VecStore<E>>| {
+        let s = mmr.store();
+        (
+            s.data.as_ref().map_or(0, Vec::capacity),
+            s.hashes.capacity(),
+        )
+    };
+
+    let mut reallocs = 0;
+    let mut prev = capacity(&mmr);
+
+    for n in 0..1_000_000 {
+        mmr.append(&n).unwrap();
+
+        let cap = capacity(&mmr);
+        reallocs += usize::from(cap.0 != prev.0) + usize::from(cap.1 != prev.1);
+        prev = cap;
+    }
+
+    reallocs
+}
+
 fn bench(c: &mut Criterion) {
     c.bench_function("MMR append", |b| {
         b.iter(|| {
@@ -46,6 +78,40 @@ fn bench(c: &mut Criterion) {
         });
     });
 
+    println!(
+        "MMR append 1M reallocations: without reserve {}, with reserve {}",
+        count_reallocations(false),
+        count_reallocations(true)
+    );
+
+    let mut group = c.benchmark_group("MMR append 1M");
+    group.sample_size(10);
+
+    group.bench_function("without reserve", |b| {
+        b.iter(|| {
+            let s = VecStore::<E>::new();
+            let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+            for n in 0..1_000_000 {
+                mmr.append(&n).unwrap();
+            }
+        });
+    });
+
+    group.bench_function("with reserve", |b| {
+        b.iter(|| {
+            let s = VecStore::<E>::new();
+            let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+            mmr.reserve(1_000_000);
+
+            for n in 0..1_000_000 {
+                mmr.append(&n).unwrap();
+            }
+        });
+    });
+
+    group.finish();
+
     c.bench_function("MMR proof", |b| {
         let mmr = make_mmr(11);
         let leafs = vec![1u64, 2, 4, 5, 8, 9, 11, 12, 16, 17, 19];
@@ -0,0 +1,181 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle batch proof for multiple MMR leaves
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+use crate::{error::Error, utils, vec, Hash, Hashable, IndexEncoding, Vec};
+
+/// A MMR membership proof for multiple leaves at once.
+///
+/// Nodes shared by the paths of several leaves are included only once. The proof
+/// path contains, peak by peak from left to right, the sibling hashes needed to fold
+/// the proven leaves up to their peak, bottom up and left to right. Peaks without any
+/// proven leaf are included as is.
+#[derive(Clone, Debug, PartialEq, Encode, Decode, TypeInfo)]
+pub struct BatchProof {
+    pub mmr_size: u64,
+    pub path: Vec<Hash>,
+}
+
+impl BatchProof {
+    /// Verify that each `(pos, elem)` tuple in `leaves` is a MMR leaf at position `pos`
+    /// given the root hash `root`.
+    ///
+    /// Leaf positions have to be in strictly ascending order.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify<T>(&self, root: Hash, leaves: &[(u64, T)]) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        self.verify_with_index_encoding(root, leaves, IndexEncoding::default())
+    }
+
+    /// Verify that each `(pos, elem)` tuple in `leaves` is a MMR leaf at position `pos`
+    /// given the root hash `root` of a MMR mixing node indices into node hashes using
+    /// `encoding`.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_with_index_encoding<T>(
+        &self,
+        root: Hash,
+        leaves: &[(u64, T)],
+        encoding: IndexEncoding,
+    ) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        let leaves = leaves
+            .iter()
+            .map(|(pos, elem)| {
+                let hash = encoding.hash_with_index(pos.saturating_sub(1), &elem.encode().hash());
+                (*pos, hash)
+            })
+            .collect::<Vec<_>>();
+
+        let mut path = self.path.iter();
+
+        let peaks = fold_peaks(self.mmr_size, &leaves, encoding, |_| {
+            path.next().cloned().ok_or(Error::InvalidProofLength)
+        })?;
+
+        if path.next().is_some() {
+            return Err(Error::InvalidProofLength);
+        }
+
+        let hash = bag_peaks(self.mmr_size, &peaks, encoding).ok_or(Error::MissingRootNode)?;
+
+        if !hash.ct_eq(&root) {
+            return Err(Error::InvalidRootHash(hash, root));
+        }
+
+        Ok(true)
+    }
+}
+
+/// Fold the leaf node hashes in `leaves` up to the peaks of a MMR with `mmr_size` nodes.
+///
+/// `leaves` are `(pos, hash)` tuples in strictly ascending position order. Parent
+/// node hashes are salted with their index using `encoding`. `node` is called for
+/// every node hash which can not be calculated from `leaves`, in the order those
+/// hashes are expected to appear in a [`BatchProof`] path.
+///
+/// Return the peak hashes, left to right.
+pub(crate) fn fold_peaks<F>(
+    mmr_size: u64,
+    leaves: &[(u64, Hash)],
+    encoding: IndexEncoding,
+    mut node: F,
+) -> Result<Vec<Hash>, Error>
+where
+    F: FnMut(u64) -> Result<Hash, Error>,
+{
+    utils::check_size(mmr_size)?;
+
+    for (i, (pos, _)) in leaves.iter().enumerate() {
+        utils::check_pos(*pos, mmr_size)?;
+
+        if !utils::is_leaf(pos - 1) {
+            return Err(Error::ExpectingLeafNode(*pos));
+        }
+
+        if i > 0 && leaves[i - 1].0 >= *pos {
+            return Err(Error::UnsortedPositions);
+        }
+    }
+
+    let mut rest = leaves;
+    let mut peaks = vec![];
+
+    for peak in utils::peaks(mmr_size) {
+        let n = rest.iter().take_while(|(pos, _)| *pos <= peak).count();
+        let (below, tail) = rest.split_at(n);
+        rest = tail;
+
+        if below.is_empty() {
+            peaks.push(node(peak)?);
+            continue;
+        }
+
+        // all nodes on a level are at the same height and in ascending order
+        let mut level = below.to_vec();
+
+        while level[0].0 != peak {
+            let mut next = Vec::with_capacity(level.len());
+            let mut i = 0;
+
+            while i < level.len() {
+                let (pos, hash) = level[i];
+                let (parent, sibling) = utils::family(pos);
+
+                let children = if sibling > pos {
+                    if level.get(i + 1).map(|n| n.0) == Some(sibling) {
+                        i += 1;
+                        (hash, level[i].1)
+                    } else {
+                        (hash, node(sibling)?)
+                    }
+                } else {
+                    (node(sibling)?, hash)
+                };
+
+                next.push((
+                    parent,
+                    encoding.combine_indexed(parent - 1, &children.0, &children.1),
+                ));
+                i += 1;
+            }
+
+            level = next;
+        }
+
+        peaks.push(level[0].1);
+    }
+
+    Ok(peaks)
+}
+
+/// Bag `peaks` of a MMR with `mmr_size` nodes into a single root hash.
+///
+/// Peaks are expected left to right and are bagged right to left, just like
+/// [`crate::MerkleMountainRange::root`] does.
+pub(crate) fn bag_peaks(mmr_size: u64, peaks: &[Hash], encoding: IndexEncoding) -> Option<Hash> {
+    peaks.iter().rev().fold(None, |hash, &peak| match hash {
+        None => Some(peak),
+        Some(h) => Some(encoding.hash_with_index(mmr_size, &Hash::combine(&peak, &h))),
+    })
+}
@@ -0,0 +1,98 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle proof verification cache
+
+use crate::{BTreeSet, Hash, VecDeque};
+
+#[cfg(test)]
+#[path = "cache_tests.rs"]
+mod tests;
+
+type Key = (Hash, u64, Hash);
+
+/// A bounded cache of successful proof verifications.
+///
+/// Entries are keyed by `(root, pos, leaf_hash)`, where `leaf_hash` is the hash of
+/// the encoded leaf. Only successful verifications are cached. A cached entry states
+/// that the leaf is part of the MMR with the given root, no matter which proof was
+/// used to show it. Caching failures could reject a valid proof after an invalid one
+/// for the same leaf was seen.
+///
+/// Once the cache is full, the oldest entry is evicted.
+#[derive(Clone, Debug)]
+pub struct VerifiedProofCache {
+    capacity: usize,
+    entries: BTreeSet<Key>,
+    // insertion order of `entries`, oldest first
+    order: VecDeque<Key>,
+    hits: u64,
+}
+
+impl VerifiedProofCache {
+    /// Return a new cache holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        VerifiedProofCache {
+            capacity,
+            entries: BTreeSet::new(),
+            order: VecDeque::with_capacity(capacity),
+            hits: 0,
+        }
+    }
+
+    /// Return the number of cache hits so far.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Return the number of cached entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true`, if the cache does not contain any entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return `true` and count a hit, if `key` is cached.
+    pub(crate) fn lookup(&mut self, key: &Key) -> bool {
+        if self.entries.contains(key) {
+            self.hits += 1;
+            return true;
+        }
+
+        false
+    }
+
+    /// Insert `key`, evicting the oldest entry if the cache is full.
+    ///
+    /// Inserting an already cached `key` is a no-op.
+    pub(crate) fn insert(&mut self, key: Key) {
+        if self.capacity == 0 || self.entries.contains(&key) {
+            return;
+        }
+
+        if self.entries.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.entries.insert(key);
+        self.order.push_back(key);
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Verified proof cache unit tests
+
+use super::VerifiedProofCache;
+use crate::Hash;
+
+fn key(n: u8) -> (Hash, u64, Hash) {
+    (Hash([n; 32]), u64::from(n), Hash([n; 32]))
+}
+
+#[test]
+fn insert_duplicate_works() {
+    let mut cache = VerifiedProofCache::new(2);
+
+    cache.insert(key(1));
+    cache.insert(key(1));
+    assert_eq!(1, cache.len());
+
+    cache.insert(key(2));
+    cache.insert(key(1));
+    assert_eq!(2, cache.len());
+
+    // the oldest entry is evicted, a duplicate insert does not refresh it
+    cache.insert(key(3));
+    assert_eq!(2, cache.len());
+    assert!(!cache.lookup(&key(1)));
+    assert!(cache.lookup(&key(2)));
+    assert!(cache.lookup(&key(3)));
+    assert_eq!(2, cache.hits());
+}
+
+#[test]
+fn zero_capacity_works() {
+    let mut cache = VerifiedProofCache::new(0);
+
+    cache.insert(key(1));
+    assert!(cache.is_empty());
+    assert!(!cache.lookup(&key(1)));
+}
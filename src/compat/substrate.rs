@@ -0,0 +1,161 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate `pallet-mmr` proof verification
+//!
+//! `pallet-mmr` is based on the `ckb-merkle-mountain-range` crate. Compared to
+//! arber, it differs as follows:
+//!
+//! - node positions are `0` based and node hashes are not salted with their index
+//! - a leaf `elem` is stored as `H(elem.encode())`
+//! - a parent with children `l` and `r` is stored as `H(l || r)`
+//! - the root is calculated by bagging the peaks from right to left. Starting with
+//!   the rightmost peak `h`, each peak `p` to the left is folded in as `h = H(h || p)`
+//! - a proof contains the peaks left of the leaf peak, from left to right, followed by
+//!   the siblings from the leaf up to its peak, followed by the bagged peaks right of
+//!   the leaf peak, if any
+//!
+//! Only `pallet-mmr` instances using `BlakeTwo256`, i.e. a 32 byte Blake2b hash, as
+//! their `Hashing` are supported.
+
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+use crate::{utils, Error, Hash, Vec};
+
+/// A `pallet-mmr` membership proof for a single leaf.
+///
+/// This mirrors the `Proof` type of `pallet-mmr` for a single leaf index.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct SubstrateProof {
+    /// Number of leaves in the MMR the proof was generated for
+    pub leaf_count: u64,
+    /// Proof items, see the [module level documentation](self) for their order
+    pub items: Vec<Hash>,
+}
+
+/// Verify that `leaf` is the leaf with index `leaf_index` of a `pallet-mmr` MMR with
+/// root `root`, using `proof`.
+///
+/// Note that `leaf_index` is a leaf index, as used by `pallet-mmr`, not a MMR node
+/// position. [`Error::LeafIndexOutOfRange`] is returned, if `leaf_index` is not a
+/// leaf of the MMR and [`Error::InvalidProofLength`], if `proof` contains too few or
+/// too many items. Like for [`MerkleProof::verify`](crate::MerkleProof::verify), a
+/// root hash mismatch is reported as [`Error::InvalidRootHash`].
+#[must_use = "a failed verification is reported as an error"]
+pub fn verify_substrate_proof<T>(
+    root: Hash,
+    leaf: &T,
+    leaf_index: u64,
+    proof: &SubstrateProof,
+) -> Result<bool, Error>
+where
+    T: Encode,
+{
+    if leaf_index >= proof.leaf_count {
+        return Err(Error::LeafIndexOutOfRange(leaf_index, proof.leaf_count));
+    }
+
+    let mut items = proof.items.iter();
+    let mut peak_hashes = Vec::new();
+    // index of the leaf, i.e. the size of the MMR before the leaf was appended
+    let leaf_idx = utils::mmr_size(leaf_index);
+    let mut found = false;
+
+    for peak_idx in utils::peaks(utils::mmr_size(proof.leaf_count))
+        .into_iter()
+        .map(|pos| pos - 1)
+    {
+        if found {
+            // all peaks right of the leaf peak are bagged into a single proof item
+            peak_hashes.push(*items.next().ok_or(Error::InvalidProofLength)?);
+            break;
+        }
+
+        if leaf_idx <= peak_idx {
+            peak_hashes.push(peak_root(leaf, leaf_idx, peak_idx, &mut items)?);
+            found = true;
+        } else {
+            peak_hashes.push(*items.next().ok_or(Error::InvalidProofLength)?);
+        }
+    }
+
+    if items.next().is_some() {
+        return Err(Error::InvalidProofLength);
+    }
+
+    let hash = bag_peaks(peak_hashes);
+
+    if root.ct_eq(&hash) {
+        Ok(true)
+    } else {
+        Err(Error::InvalidRootHash(hash, root))
+    }
+}
+
+/// Return the root of the peak at index `peak_idx`, calculated from `leaf` at index
+/// `leaf_idx` and the sibling hashes taken from `items`.
+fn peak_root<'a, T, I>(leaf: &T, leaf_idx: u64, peak_idx: u64, items: &mut I) -> Result<Hash, Error>
+where
+    T: Encode,
+    I: Iterator<Item = &'a Hash>,
+{
+    let mut hash = blake2(&leaf.encode());
+    let mut idx = leaf_idx;
+    let mut height = 0;
+
+    while idx < peak_idx {
+        let sibling = items.next().ok_or(Error::InvalidProofLength)?;
+
+        if utils::node_height(idx + 1) > height {
+            // right child, the parent immediately follows
+            hash = merge(sibling, &hash);
+            idx += 1;
+        } else {
+            // left child, the parent follows the right sibling subtree
+            hash = merge(&hash, sibling);
+            idx += 2 << height;
+        }
+
+        height += 1;
+    }
+
+    Ok(hash)
+}
+
+/// Bag `peaks` from right to left.
+fn bag_peaks(mut peaks: Vec<Hash>) -> Hash {
+    while peaks.len() > 1 {
+        let right = peaks.pop().unwrap_or_default();
+        let left = peaks.pop().unwrap_or_default();
+        peaks.push(merge(&right, &left));
+    }
+
+    peaks.pop().unwrap_or_default()
+}
+
+fn merge(left: &Hash, right: &Hash) -> Hash {
+    let mut data = left.0.to_vec();
+    data.extend_from_slice(&right.0);
+    blake2(&data)
+}
+
+fn blake2(data: &[u8]) -> Hash {
+    let mut h = Blake2b::<U32>::new();
+    h.update(data);
+    Hash::from_vec(&h.finalize())
+}
@@ -0,0 +1,131 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle-Mountain-Range store counting store operations
+
+use core::cell::Cell;
+
+use codec::{Decode, Encode};
+
+use crate::{Hash, Result, Store, Vec};
+
+/// A store wrapping an `inner` store, counting node reads and appends.
+///
+/// Every node hash read counts as a single read, no matter whether it is read by
+/// [`Store::hash_at`], [`Store::get_many`], [`Store::peak_hashes`] or
+/// [`Store::contains`]. Every [`Store::append`] counts as a single write.
+pub struct CountingStore<S> {
+    inner: S,
+    reads: Cell<u64>,
+    writes: Cell<u64>,
+}
+
+impl<S> CountingStore<S> {
+    /// Return a store wrapping `inner`, with all counters set to zero.
+    pub fn new(inner: S) -> Self {
+        CountingStore {
+            inner,
+            reads: Cell::new(0),
+            writes: Cell::new(0),
+        }
+    }
+
+    /// Return the number of node hashes read so far.
+    pub fn reads(&self) -> u64 {
+        self.reads.get()
+    }
+
+    /// Return the number of appends so far.
+    pub fn writes(&self) -> u64 {
+        self.writes.get()
+    }
+
+    /// Reset all counters to zero.
+    pub fn reset(&self) {
+        self.reads.set(0);
+        self.writes.set(0);
+    }
+
+    /// Return a reference to the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Return the wrapped store.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn count_reads(&self, n: usize) {
+        self.reads.set(self.reads.get() + n as u64);
+    }
+}
+
+impl<T, S> Store<T> for CountingStore<S>
+where
+    T: Clone + Decode + Encode,
+    S: Store<T>,
+{
+    fn hash_at(&self, index: u64) -> Result<Hash> {
+        self.count_reads(1);
+        self.inner.hash_at(index)
+    }
+
+    fn get_many(&self, indices: &[u64]) -> Result<Vec<Hash>> {
+        self.count_reads(indices.len());
+        self.inner.get_many(indices)
+    }
+
+    fn peak_hashes(&self, size: u64) -> Result<Vec<Hash>> {
+        let peaks = self.inner.peak_hashes(size)?;
+        self.count_reads(peaks.len());
+        Ok(peaks)
+    }
+
+    fn contains(&self, index: u64) -> bool {
+        self.count_reads(1);
+        self.inner.contains(index)
+    }
+
+    fn append(&mut self, elem: &T, hashes: &[Hash]) -> Result<()> {
+        self.writes.set(self.writes.get() + 1);
+        self.inner.append(elem, hashes)
+    }
+
+    fn undo_append(&mut self, nodes: u64) -> Result<()> {
+        self.inner.undo_append(nodes)
+    }
+
+    fn len(&self) -> u64 {
+        self.inner.len()
+    }
+
+    fn reserve(&mut self, leaves: u64, nodes: u64) {
+        self.inner.reserve(leaves, nodes)
+    }
+
+    fn prune(&mut self, retain: &[u64]) -> Result<()> {
+        self.inner.prune(retain)
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit()
+    }
+
+    fn leaf_data(&self) -> Result<Vec<T>> {
+        self.inner.leaf_data()
+    }
+}
@@ -25,22 +25,74 @@ use displaydoc::Display;
 
 use crate::{Hash, String};
 
+#[cfg(all(test, feature = "std"))]
+#[path = "error_tests.rs"]
+mod tests;
+
 #[derive(Display, Debug, PartialEq, Eq, Clone)]
 pub enum Error {
+    #[displaydoc("leaf data not retained by the store")]
+    DataNotRetained,
+    #[displaydoc("empty leaf")]
+    EmptyLeaf,
     #[displaydoc("expecting leaf node at pos: {0}")]
     ExpectingLeafNode(u64),
+    #[displaydoc("future MMR size: {0} > {1}")]
+    FutureSize(u64, u64),
+    #[displaydoc("invalid append delta at pos: {0}, expected pos: {1}")]
+    InvalidDelta(u64, u64),
+    #[displaydoc("invalid append delta length: {0}, expected: {1}")]
+    InvalidDeltaLength(u64, u64),
     #[displaydoc("invalid hex string: {0}")]
     InvalidHexString(String),
+    #[displaydoc("invalid leaf encoding")]
+    InvalidLeafEncoding,
     #[displaydoc("invalid node hash at idx {0}: {1} != {2}")]
     InvalidNodeHash(u64, Hash, Hash),
     #[displaydoc("invalid node height: {0}")]
     InvalidNodeHeight(u64),
+    #[displaydoc("invalid peak count: {0}, expected: {1}")]
+    InvalidPeakCount(u64, u64),
+    #[displaydoc("invalid peak position: {0}")]
+    InvalidPeakPosition(u64),
+    #[displaydoc("invalid peaks encoding")]
+    InvalidPeaksEncoding,
+    #[displaydoc("invalid proof encoding")]
+    InvalidProofEncoding,
+    #[displaydoc("invalid proof length")]
+    InvalidProofLength,
     #[displaydoc("invalid root hash: {0} != {1}")]
     InvalidRootHash(Hash, Hash),
+    #[displaydoc("leaf hash mismatch: {0} != {1}")]
+    LeafHashMismatch(Hash, Hash),
+    #[displaydoc("leaf index out of range: {0} >= {1}")]
+    LeafIndexOutOfRange(u64, u64),
     #[displaydoc("missing hash at index: {0}")]
     MissingHashAtIndex(u64),
+    #[displaydoc("missing proof node at pos: {0}")]
+    MissingProofNode(u64),
     #[displaydoc("missing root node")]
     MissingRootNode,
+    #[displaydoc("MMR size mismatch: {0} != {1}")]
+    MmrSizeMismatch(u64, u64),
+    #[displaydoc("position out of range: {0} > {1}")]
+    PositionOutOfRange(u64, u64),
+    #[displaydoc("proof too long: {0} > {1}")]
+    ProofTooLong(u64, u64),
+    #[displaydoc("pruned node at index: {0}")]
+    Pruned(u64),
+    #[displaydoc("store out of sync, MMR size: {0} != store length: {1}")]
+    StoreDesync(u64, u64),
+    #[displaydoc("store io error: {0}")]
+    StoreIo(String),
+    #[displaydoc("peak positions not in strictly ascending order")]
+    UnsortedPeaks,
+    #[displaydoc("positions not in strictly ascending order")]
+    UnsortedPositions,
+    #[displaydoc("unstable MMR size: {0}")]
+    UnstableSize(u64),
+    #[displaydoc("invalid zero position")]
+    ZeroPosition,
 }
 
 unsafe impl Send for Error {}
@@ -0,0 +1,55 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle-Mountain-Range error unit tests
+
+use std::io;
+
+use crate::{Error, String};
+
+#[test]
+fn into_io_error_works() {
+    let err: io::Error = Error::StoreIo(String::from("disk full")).into();
+
+    assert_eq!(io::ErrorKind::Other, err.kind());
+    assert_eq!("store io error: disk full", err.to_string());
+    assert_eq!(
+        Some(&Error::StoreIo(String::from("disk full"))),
+        err.get_ref().and_then(|e| e.downcast_ref::<Error>())
+    );
+
+    let err: io::Error = Error::InvalidProofEncoding.into();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+    let err: io::Error = Error::InvalidPeaksEncoding.into();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+    let err: io::Error = Error::Pruned(3).into();
+    assert_eq!(io::ErrorKind::NotFound, err.kind());
+
+    let err: io::Error = Error::ZeroPosition.into();
+    assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+}
+
+#[test]
+fn question_mark_into_io_error_works() {
+    fn f() -> io::Result<()> {
+        Err(Error::StoreIo(String::from("closed")))?;
+        Ok(())
+    }
+
+    assert_eq!(io::ErrorKind::Other, f().unwrap_err().kind());
+}
@@ -19,37 +19,28 @@
 use core::{
     cmp::min,
     convert::AsRef,
-    fmt::{self, Write},
+    fmt,
+    ops::{BitXor, BitXorAssign},
 };
 
 #[cfg(not(feature = "std"))]
 use alloc::string::ToString;
 
 use blake2::{digest::consts::U32, Blake2b, Digest};
-use codec::{Decode, Encode, EncodeLike};
+use codec::{Compact, Decode, Encode, EncodeLike};
 use scale_info::TypeInfo;
 
-use crate::{Error, String, Vec};
+use crate::{BTreeMap, Error, String, Vec};
 
 #[cfg(test)]
 #[path = "hash_tests.rs"]
 mod tests;
 
-macro_rules! to_hex {
-    ($bytes:expr) => {{
-        let mut s = String::with_capacity(64);
-
-        for b in $bytes {
-            write!(&mut s, "{:02x}", b)?
-        }
-
-        Ok(s)
-    }};
-}
-
 /// Generic hash type which should be compatible with most hashes used
 /// within the blockchain domain.
-#[derive(Copy, Clone, PartialEq, Eq, Encode, Decode, Default, TypeInfo)]
+///
+/// Hashing a `Hash` hashes its byte array, allowing to use it as key in hash maps and sets.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Encode, Decode, Default, TypeInfo)]
 pub struct Hash(pub [u8; 32]);
 
 impl EncodeLike<[u8; 32]> for Hash {}
@@ -61,8 +52,7 @@ impl fmt::Debug for Hash {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         const DISP_SIZE: usize = 12;
 
-        let hex = to_hex!(&self.0)?;
-        write!(f, "{}", &hex[..DISP_SIZE])
+        write!(f, "{}", &self.to_hex()[..DISP_SIZE])
     }
 }
 
@@ -72,6 +62,25 @@ impl fmt::Display for Hash {
     }
 }
 
+/// Byte-wise XOR of two hashes.
+impl BitXor for Hash {
+    type Output = Hash;
+
+    fn bitxor(mut self, rhs: Hash) -> Hash {
+        self ^= rhs;
+        self
+    }
+}
+
+impl BitXorAssign for Hash {
+    fn bitxor_assign(&mut self, rhs: Hash) {
+        self.0
+            .iter_mut()
+            .zip(rhs.0.iter())
+            .for_each(|(a, b)| *a ^= b);
+    }
+}
+
 impl AsRef<[u8]> for Hash {
     fn as_ref(&self) -> &[u8] {
         &self.0
@@ -82,6 +91,20 @@ impl Hash {
     /// 32 byte hash
     pub const LEN: usize = 32;
 
+    /// Return the lowercase hex representation of the hash, without a `0x` prefix.
+    pub fn to_hex(&self) -> String {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+
+        let mut s = String::with_capacity(2 * Hash::LEN);
+
+        for b in self.0 {
+            s.push(HEX[(b >> 4) as usize] as char);
+            s.push(HEX[(b & 0xf) as usize] as char);
+        }
+
+        s
+    }
+
     /// Return a hash initialized from `v`.
     ///
     /// At most, up to [`Hash::LEN`] bytes will be copied from `v`. If `v` has less
@@ -93,6 +116,56 @@ impl Hash {
         Hash(h)
     }
 
+    /// Return the first `n` bytes of the hash. `n` is clamped to [`Hash::LEN`].
+    pub fn prefix(&self, n: usize) -> &[u8] {
+        &self.0[..min(n, Hash::LEN)]
+    }
+
+    /// Return the first 8 bytes of the hash as a little-endian `u64`.
+    pub fn prefix_u64(&self) -> u64 {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&self.0[..8]);
+        u64::from_le_bytes(buf)
+    }
+
+    /// Return `true`, if both hashes are equal, comparing them in constant time.
+    ///
+    /// With the `constant-time` feature, `subtle::ConstantTimeEq` is used. Otherwise,
+    /// all bytes are compared without short-circuiting, which is a best effort only.
+    pub fn ct_eq(&self, other: &Hash) -> bool {
+        #[cfg(feature = "constant-time")]
+        {
+            use subtle::ConstantTimeEq;
+
+            self.0.ct_eq(&other.0).into()
+        }
+
+        #[cfg(not(feature = "constant-time"))]
+        {
+            self.0
+                .iter()
+                .zip(other.0.iter())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                == 0
+        }
+    }
+
+    /// Return the hash of the `left` and `right` child hashes.
+    ///
+    /// This is the same as `(left, right).hash()`.
+    pub fn combine(left: &Hash, right: &Hash) -> Hash {
+        (*left, *right).hash()
+    }
+
+    /// Return the hash of the `left` and `right` child hashes, salted with `idx`.
+    ///
+    /// This is how MMR parent nodes at index `idx` are hashed, as well as how peaks
+    /// are bagged, using the MMR size as `idx`. The index is encoded little-endian,
+    /// see [`IndexEncoding::combine_indexed`] for other byte orders.
+    pub fn combine_indexed(idx: u64, left: &Hash, right: &Hash) -> Hash {
+        IndexEncoding::LittleEndian.combine_indexed(idx, left, right)
+    }
+
     /// Retrun a hash initialized from string `hex`.
     ///
     /// An error is returned, if `hex` is not a well-formed hex string like `"0xcafe"`.
@@ -102,12 +175,70 @@ impl Hash {
             Err(s) => Err(Error::InvalidHexString(s)),
         }
     }
+
+    /// Return a hash initialized from string `hex` at compile time.
+    ///
+    /// Unlike [`Hash::from_hex`], `hex` has to be exactly [`Hash::LEN`] bytes long,
+    /// i.e. 64 hex digits, optionally prefixed with `0x`. This function panics on
+    /// malformed input, which is a compile time error when used in a const context.
+    /// See the [`hash!`](crate::hash!) macro.
+    pub const fn from_hex_const(hex: &str) -> Hash {
+        let bytes = hex.as_bytes();
+        let start = if bytes.len() >= 2 && bytes[0] == b'0' && bytes[1] == b'x' {
+            2
+        } else {
+            0
+        };
+
+        if bytes.len() - start != 2 * Hash::LEN {
+            panic!("expecting 64 hex digits");
+        }
+
+        let mut h = [0u8; Hash::LEN];
+        let mut i = 0;
+
+        while i < Hash::LEN {
+            let hi = hex_digit(bytes[start + 2 * i]);
+            let lo = hex_digit(bytes[start + 2 * i + 1]);
+            h[i] = (hi << 4) | lo;
+            i += 1;
+        }
+
+        Hash(h)
+    }
+}
+
+const fn hex_digit(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        b'A'..=b'F' => c - b'A' + 10,
+        _ => panic!("invalid hex digit"),
+    }
+}
+
+/// Return a [`struct@Hash`] parsed from a hex string literal at compile time.
+///
+/// The literal has to consist of exactly 64 hex digits, optionally prefixed with
+/// `0x`, otherwise compilation fails.
+///
+/// ```
+/// use arber::{hash, Hash};
+///
+/// const ROOT: Hash = hash!("0x0102030405060708091011121314151617181920212223242526272829303132");
+/// ```
+#[macro_export]
+macro_rules! hash {
+    ($hex:expr) => {{
+        const HASH: $crate::Hash = $crate::Hash::from_hex_const($hex);
+        HASH
+    }};
 }
 
 fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
     let hex = hex.trim().trim_start_matches("0x");
 
-    if hex.len() % 2 != 0 {
+    if !hex.len().is_multiple_of(2) {
         Err(hex.to_string())
     } else {
         (0..hex.len())
@@ -149,6 +280,23 @@ impl Hashable for u64 {
     }
 }
 
+impl Hashable for bool {
+    fn hash(&self) -> Hash {
+        let mut h = Blake2b::<U32>::new();
+        h.update([*self as u8]);
+        let v = h.finalize();
+        Hash::from_vec(&v)
+    }
+}
+
+impl Hashable for () {
+    fn hash(&self) -> Hash {
+        let h = Blake2b::<U32>::new();
+        let v = h.finalize();
+        Hash::from_vec(&v)
+    }
+}
+
 impl Hashable for Hash {
     /// Return the hash, without hashing again.
     fn hash(&self) -> Hash {
@@ -156,6 +304,10 @@ impl Hashable for Hash {
     }
 }
 
+/// Hash the concatenation of the fixed-width hashes of both tuple elements.
+///
+/// Composite types must never hash the raw bytes of variable-length elements.
+/// Otherwise, `(a, b)` and `(c, d)` would collide whenever `a || b == c || d`.
 impl<A, B> Hashable for (A, B)
 where
     A: Hashable,
@@ -170,6 +322,53 @@ where
     }
 }
 
+/// Hash the SCALE compact encoded element count, followed by the fixed-width hashes
+/// of all elements in index order.
+///
+/// Prefixing the count keeps a two element slice `[a, b]` from hashing the same as
+/// the tuple `(a, b)` or [`Hash::combine`], and an empty slice from hashing the same
+/// as `()`.
+impl<T> Hashable for [T]
+where
+    T: Hashable,
+{
+    fn hash(&self) -> Hash {
+        let mut h = Blake2b::<U32>::new();
+        h.update(Compact(self.len() as u64).encode());
+        self.iter().for_each(|e| h.update(e.hash()));
+        let v = h.finalize();
+        Hash::from_vec(&v)
+    }
+}
+
+/// Hash an array like the equivalent slice.
+impl<T, const N: usize> Hashable for [T; N]
+where
+    T: Hashable,
+{
+    fn hash(&self) -> Hash {
+        self[..].hash()
+    }
+}
+
+/// Hash the concatenation of the hashes of all `(key, value)` entries in key order.
+///
+/// The hash does not depend on insertion order, which makes it suitable for
+/// commitments to a set of key value pairs.
+impl<K, V> Hashable for BTreeMap<K, V>
+where
+    K: Hashable + Ord,
+    V: Hashable,
+{
+    fn hash(&self) -> Hash {
+        let mut h = Blake2b::<U32>::new();
+        self.iter()
+            .for_each(|(k, v)| h.update((k.hash(), v.hash()).hash()));
+        let v = h.finalize();
+        Hash::from_vec(&v)
+    }
+}
+
 /// Return the hash of `idx` and `hash`.
 ///
 /// This function is used to avoid collisions among leaf data hashes themselves.
@@ -180,3 +379,71 @@ pub fn hash_with_index(idx: u64, hash: &Hash) -> Hash {
     let v = h.finalize();
     Hash::from_vec(&v)
 }
+
+/// Return the leaf node hash of `elem` at position `pos`, i.e.
+/// `hash_with_index(pos - 1, &elem.hash())`.
+///
+/// The leaf data hash is [`Hashable::hash`] of `elem`, like for
+/// [`MerkleProof::verify_typed`](crate::MerkleProof::verify_typed). For the leaf hash
+/// of an element appended with [`MerkleMountainRange::append`](crate::MerkleMountainRange::append),
+/// pass its SCALE encoding or use
+/// [`MerkleMountainRange::expected_leaf_hash`](crate::MerkleMountainRange::expected_leaf_hash).
+///
+/// Positions are `1` based, hence [`Error::ZeroPosition`] is returned for `pos == 0`.
+pub fn leaf_hash(pos: u64, elem: &dyn Hashable) -> Result<Hash, Error> {
+    let idx = pos.checked_sub(1).ok_or(Error::ZeroPosition)?;
+
+    Ok(hash_with_index(idx, &elem.hash()))
+}
+
+/// Return the hash of `idx` and `hash`, with `idx` being a 128 bit index.
+///
+/// This is meant for layered designs, where node indices may exceed the `u64` range.
+/// Note that `idx` is always encoded as 16 bytes little-endian, hence the hash differs
+/// from [`hash_with_index`] even if `idx` fits into a `u64`. Do not mix both within
+/// the same MMR.
+pub fn hash_with_index_u128(idx: u128, hash: &Hash) -> Hash {
+    let mut h = Blake2b::<U32>::new();
+    h.update(idx.to_le_bytes());
+    h.update(hash);
+    let v = h.finalize();
+    Hash::from_vec(&v)
+}
+
+/// Return the hash of `idx` and `hash`, with `idx` encoded big-endian.
+///
+/// Same as [`hash_with_index`], but for chains salting node hashes with a
+/// big-endian index.
+pub fn hash_with_index_be(idx: u64, hash: &Hash) -> Hash {
+    let mut h = Blake2b::<U32>::new();
+    h.update(idx.to_be_bytes());
+    h.update(hash);
+    let v = h.finalize();
+    Hash::from_vec(&v)
+}
+
+/// Byte order of the node index mixed into MMR node hashes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IndexEncoding {
+    /// Use [`hash_with_index`]
+    #[default]
+    LittleEndian,
+    /// Use [`hash_with_index_be`]
+    BigEndian,
+}
+
+impl IndexEncoding {
+    /// Return the hash of `idx` and `hash`, with `idx` encoded in this byte order.
+    pub fn hash_with_index(self, idx: u64, hash: &Hash) -> Hash {
+        match self {
+            IndexEncoding::LittleEndian => hash_with_index(idx, hash),
+            IndexEncoding::BigEndian => hash_with_index_be(idx, hash),
+        }
+    }
+
+    /// Return the hash of the `left` and `right` child hashes, salted with `idx`
+    /// encoded in this byte order. See [`Hash::combine_indexed`].
+    pub fn combine_indexed(self, idx: u64, left: &Hash, right: &Hash) -> Hash {
+        self.hash_with_index(idx, &Hash::combine(left, right))
+    }
+}
@@ -16,7 +16,10 @@
 
 //! Hash type unit tests
 
-use super::{hash_with_index, Error, Hash, Hashable};
+use super::{
+    hash_with_index, hash_with_index_be, hash_with_index_u128, leaf_hash, Error, Hash, Hashable,
+    IndexEncoding, ZERO_HASH,
+};
 
 macro_rules! hash_two {
     ($a:expr, $b:expr) => {{
@@ -60,6 +63,25 @@ fn from_hex_works() {
     assert_eq!(want, got);
 }
 
+#[test]
+fn to_hex_works() {
+    let hashes = [
+        Hash::from_vec(&[]),
+        Hash::from_vec(&[222, 173, 202, 254, 186, 190]),
+        Hash([0xff; 32]),
+        vec![0u8, 1, 2].hash(),
+        42u64.hash(),
+    ];
+
+    for h in hashes {
+        let want = h.0.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+
+        assert_eq!(want, h.to_hex());
+        assert_eq!(want[..12], format!("{}", h));
+        assert_eq!(h, Hash::from_hex(&h.to_hex()).unwrap());
+    }
+}
+
 #[test]
 fn from_hex_error() {
     let want = Error::InvalidHexString("000".to_string());
@@ -118,6 +140,23 @@ fn u32_hash_works() {
     assert_ne!(h2, h3);
 }
 
+#[test]
+fn bool_hash_works() {
+    assert_eq!(true.hash(), true.hash());
+    assert_ne!(true.hash(), false.hash());
+
+    // same as the SCALE encoding of a bool
+    assert_eq!(vec![1u8].hash(), true.hash());
+    assert_eq!(vec![0u8].hash(), false.hash());
+}
+
+#[test]
+fn unit_hash_works() {
+    assert_eq!(().hash(), ().hash());
+    assert_eq!(Vec::<u8>::new().hash(), ().hash());
+    assert_ne!(false.hash(), ().hash());
+}
+
 #[test]
 fn tuple_hash_works() {
     let h1 = (1u64, vec![0u8; 10]).hash();
@@ -129,6 +168,58 @@ fn tuple_hash_works() {
     assert_ne!(h2, h3);
 }
 
+#[test]
+fn array_hash_works() {
+    let a = 1u32;
+    let b = 2u32;
+
+    assert_eq!([a, b].hash(), [a, b][..].hash());
+    assert_ne!([a, b].hash(), [b, a].hash());
+
+    let v = [vec![1u8, 2], vec![3u8]].to_vec();
+
+    assert_eq!(v[..].hash(), [vec![1u8, 2], vec![3u8]].hash());
+    assert_ne!(v[..].hash(), [vec![1u8], vec![2u8, 3]].hash());
+}
+
+#[test]
+fn array_hash_no_collision() {
+    let a = 1u32.hash();
+    let b = 2u32.hash();
+
+    assert_ne!([a, b].hash(), (a, b).hash());
+    assert_ne!([a, b].hash(), Hash::combine(&a, &b));
+    assert_ne!([0u32; 0].hash(), ().hash());
+    assert_ne!([a].hash(), a.hash());
+
+    // the element count is SCALE compact encoded, i.e. `2 << 2`
+    assert_eq!(
+        [a, b].hash(),
+        Hash::from_vec(&{
+            use blake2::{digest::consts::U32, Blake2b, Digest};
+
+            let mut h = Blake2b::<U32>::new();
+            h.update([8u8]);
+            h.update(a);
+            h.update(b);
+            h.finalize()
+        })
+    );
+}
+
+#[test]
+fn tuple_hash_no_concat_collision() {
+    // the raw bytes of both tuples concatenate to [1, 2, 3]
+    let h1 = (vec![1u8, 2], vec![3u8]).hash();
+    let h2 = (vec![1u8], vec![2u8, 3]).hash();
+
+    assert_ne!(h1, h2);
+
+    let h3 = hash_two!(vec![1u8, 2].hash(), vec![3u8].hash());
+
+    assert_eq!(h1, h3);
+}
+
 #[test]
 fn hash_two_works() {
     let h1 = hash_two!(1u64.to_le_bytes(), vec![0u8; 10]);
@@ -159,3 +250,174 @@ fn hash_with_index_works() {
     let got = hash_with_index(2, &h2);
     assert_eq!(want, got);
 }
+
+#[test]
+fn hash_with_index_be_works() {
+    let h = vec![1u8; 10].hash();
+
+    let want = hash_two!(1u64.to_be_bytes(), &h);
+    let got = hash_with_index_be(1, &h);
+    assert_eq!(want, got);
+    assert_ne!(hash_with_index(1, &h), got);
+
+    assert_eq!(
+        hash_with_index(1, &h),
+        IndexEncoding::LittleEndian.hash_with_index(1, &h)
+    );
+    assert_eq!(got, IndexEncoding::BigEndian.hash_with_index(1, &h));
+}
+
+#[test]
+fn hash_with_index_u128_works() {
+    let h = vec![1u8; 10].hash();
+
+    let want = hash_two!(1u128.to_le_bytes(), &h);
+    let got = hash_with_index_u128(1, &h);
+    assert_eq!(want, got);
+    assert_eq!(got, hash_with_index_u128(1, &h));
+
+    // same index, but a wider salt
+    assert_ne!(hash_with_index(1, &h), got);
+    assert_ne!(hash_with_index(0, &h), hash_with_index_u128(0, &h));
+
+    let idx = u64::MAX as u128 + 1;
+    assert_ne!(hash_with_index_u128(idx, &h), hash_with_index_u128(0, &h));
+    assert_eq!(
+        hash_two!(idx.to_le_bytes(), &h),
+        hash_with_index_u128(idx, &h)
+    );
+}
+
+#[test]
+fn leaf_hash_works() {
+    let elem = 42u32;
+
+    assert_eq!(Ok(hash_with_index(0, &elem.hash())), leaf_hash(1, &elem));
+    assert_eq!(Ok(hash_with_index(4, &elem.hash())), leaf_hash(5, &elem));
+    assert_ne!(leaf_hash(1, &elem), leaf_hash(2, &elem));
+    assert_eq!(Err(Error::ZeroPosition), leaf_hash(0, &elem));
+}
+
+#[test]
+fn hash_btree_map_works() {
+    use std::collections::BTreeMap;
+
+    let mut m1 = BTreeMap::new();
+    m1.insert(1u32, vec![1u8, 2]);
+    m1.insert(2u32, vec![3u8]);
+    m1.insert(3u32, vec![]);
+
+    let mut m2 = BTreeMap::new();
+    m2.insert(3u32, vec![]);
+    m2.insert(1u32, vec![1u8, 2]);
+    m2.insert(2u32, vec![3u8]);
+
+    assert_eq!(m1.hash(), m2.hash());
+
+    m2.insert(2u32, vec![4u8]);
+
+    assert_ne!(m1.hash(), m2.hash());
+
+    m2.remove(&2);
+
+    assert_ne!(m1.hash(), m2.hash());
+    assert_ne!(m2.hash(), BTreeMap::<u32, Vec<u8>>::new().hash());
+}
+
+#[test]
+fn hash_set_works() {
+    use std::collections::HashSet;
+
+    let hashes = (0..10u32).map(|i| i.hash()).collect::<Vec<_>>();
+    let set = hashes.iter().cloned().collect::<HashSet<_>>();
+
+    assert_eq!(10, set.len());
+    assert!(hashes.iter().all(|h| set.contains(h)));
+    assert!(!set.contains(&10u32.hash()));
+    assert!(!set.contains(&Hash::default()));
+}
+
+#[test]
+fn ct_eq_works() {
+    let hashes = (0..10u32).map(|i| i.hash()).collect::<Vec<_>>();
+
+    for a in &hashes {
+        for b in &hashes {
+            assert_eq!(a == b, a.ct_eq(b));
+        }
+    }
+
+    let mut h = Hash::default();
+    h.0[31] = 1;
+
+    assert!(!h.ct_eq(&Hash::default()));
+    assert!(Hash::default().ct_eq(&Hash::default()));
+}
+
+#[test]
+fn prefix_works() {
+    let h = 42u32.hash();
+
+    assert!(h.prefix(0).is_empty());
+    assert_eq!(&h.0[..4], h.prefix(4));
+    assert_eq!(Hash::LEN, h.prefix(32).len());
+    assert_eq!(Hash::LEN, h.prefix(100).len());
+}
+
+#[test]
+fn prefix_u64_works() {
+    let mut h = Hash::default();
+
+    assert_eq!(0, h.prefix_u64());
+
+    h.0[..9].copy_from_slice(&[1, 2, 0, 0, 0, 0, 0, 0x80, 0xff]);
+
+    assert_eq!(0x8000_0000_0000_0201, h.prefix_u64());
+}
+
+#[test]
+fn bitxor_works() {
+    let a = 1u32.hash();
+    let b = 2u32.hash();
+
+    assert_eq!(ZERO_HASH, a ^ a);
+    assert_eq!(a, a ^ ZERO_HASH);
+    assert_eq!(a ^ b, b ^ a);
+    assert_eq!(a, a ^ b ^ b);
+
+    let mut c = a;
+    c ^= b;
+
+    assert_eq!(a ^ b, c);
+    assert_eq!(a.0[0] ^ b.0[0], c.0[0]);
+    assert_eq!(a.0[31] ^ b.0[31], c.0[31]);
+}
+
+#[test]
+fn hash_macro_works() -> Result<(), Error> {
+    const ROOT: Hash =
+        crate::hash!("0x0102030405060708091011121314151617181920212223242526272829303132");
+    const ZERO: Hash =
+        crate::hash!("0000000000000000000000000000000000000000000000000000000000000000");
+
+    let want =
+        Hash::from_hex("0x0102030405060708091011121314151617181920212223242526272829303132")?;
+
+    assert_eq!(want, ROOT);
+    assert_eq!(Hash::default(), ZERO);
+
+    let h = 42u64.hash();
+    assert_eq!(h, Hash::from_hex_const(&h.to_hex()));
+    assert_eq!(
+        Hash::from_hex("0xABCDEF0000000000000000000000000000000000000000000000000000000000")?,
+        Hash::from_hex_const("0xABCDEF0000000000000000000000000000000000000000000000000000000000")
+    );
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "expecting 64 hex digits")]
+fn from_hex_const_fails() {
+    let _ = Hash::from_hex_const("0xcafe");
+}
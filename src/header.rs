@@ -0,0 +1,94 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle-Mountain-Range header
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+use crate::{utils, Error, Hash, Result, Vec};
+
+#[cfg(test)]
+#[path = "header_tests.rs"]
+mod tests;
+
+/// A MMR header, i.e. the MMR size together with its peak hashes.
+///
+/// The header is sufficient to calculate the MMR root and is what a verifier
+/// usually receives from an untrusted source.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct MmrHeader {
+    /// MMR size, i.e. total number of nodes
+    pub size: u64,
+    /// MMR peak hashes, left to right
+    pub peaks: Vec<Hash>,
+}
+
+impl MmrHeader {
+    /// Validate that the number of peaks is plausible for the MMR size.
+    ///
+    /// An error is returned, if the size is unstable or if the number of peaks does
+    /// not equal the number of set bits in the leaf count.
+    pub fn validate(&self) -> Result<()> {
+        utils::check_size(self.size)?;
+
+        let expected = utils::expected_peak_count(self.size);
+
+        if self.peaks.len() != expected {
+            return Err(Error::InvalidPeakCount(
+                self.peaks.len() as u64,
+                expected as u64,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Encode `peaks` into a single buffer.
+///
+/// The peak count is encoded as 4 bytes little-endian, followed by the concatenated
+/// peak hashes, i.e. `4 + 32 * n` bytes in total. Use [`decode_peaks`] to decode.
+pub fn encode_peaks(peaks: &[Hash]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(4 + Hash::LEN * peaks.len());
+
+    bytes.extend_from_slice(&(peaks.len() as u32).to_le_bytes());
+    peaks.iter().for_each(|h| bytes.extend_from_slice(&h.0));
+
+    bytes
+}
+
+/// Decode peaks encoded by [`encode_peaks`].
+///
+/// [`Error::InvalidPeaksEncoding`] is returned, if the peak count does not match the
+/// number of bytes following it.
+pub fn decode_peaks(bytes: &[u8]) -> Result<Vec<Hash>> {
+    if bytes.len() < 4 {
+        return Err(Error::InvalidPeaksEncoding);
+    }
+
+    let (count, peaks) = bytes.split_at(4);
+
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(count);
+    let count = u32::from_le_bytes(buf) as usize;
+
+    if peaks.len() != count.saturating_mul(Hash::LEN) {
+        return Err(Error::InvalidPeaksEncoding);
+    }
+
+    Ok(peaks.chunks(Hash::LEN).map(Hash::from_vec).collect())
+}
@@ -0,0 +1,116 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle-Mountain-Range header unit tests
+
+use super::{decode_peaks, encode_peaks, MmrHeader};
+use crate::{Error, Hash, Hashable, MerkleMountainRange, VecStore};
+
+type E = Vec<u8>;
+
+fn make_mmr(num_leafs: u8) -> MerkleMountainRange<E, VecStore<E>> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    (0..=num_leafs.saturating_sub(1)).for_each(|i| {
+        let n = vec![i, 10];
+        let _ = mmr.append(&n).unwrap();
+    });
+
+    mmr
+}
+
+#[test]
+fn validate_works() -> Result<(), Error> {
+    for leafs in [1, 2, 6, 7, 11] {
+        let mmr = make_mmr(leafs);
+        let header = mmr.header()?;
+
+        assert_eq!(mmr.size(), header.size);
+        assert_eq!(mmr.peaks()?, header.peaks);
+        assert_eq!(Ok(()), header.validate());
+    }
+
+    let header = MmrHeader {
+        size: 0,
+        peaks: vec![],
+    };
+
+    assert_eq!(Ok(()), header.validate());
+
+    Ok(())
+}
+
+#[test]
+fn validate_fails() -> Result<(), Error> {
+    let mut header = make_mmr(11).header()?;
+    header.peaks.pop();
+
+    assert_eq!(Err(Error::InvalidPeakCount(2, 3)), header.validate());
+
+    header.peaks.push(0u64.hash());
+    header.peaks.push(1u64.hash());
+
+    assert_eq!(Err(Error::InvalidPeakCount(4, 3)), header.validate());
+
+    let header = MmrHeader {
+        size: 5,
+        peaks: vec![],
+    };
+
+    assert_eq!(Err(Error::UnstableSize(5)), header.validate());
+
+    Ok(())
+}
+
+#[test]
+fn encode_peaks_works() -> Result<(), Error> {
+    let mmr = make_mmr(7);
+    let peaks = mmr.peaks()?;
+
+    assert_eq!(3, peaks.len());
+
+    let bytes = encode_peaks(&peaks);
+
+    assert_eq!(4 + 3 * 32, bytes.len());
+    assert_eq!(&[3, 0, 0, 0], &bytes[..4]);
+    assert_eq!(peaks, decode_peaks(&bytes)?);
+
+    assert_eq!(Vec::<Hash>::new(), decode_peaks(&encode_peaks(&[]))?);
+
+    Ok(())
+}
+
+#[test]
+fn decode_peaks_fails() {
+    let peaks = vec![1u32.hash(), 2u32.hash(), 3u32.hash()];
+    let bytes = encode_peaks(&peaks);
+
+    // missing peak count
+    assert_eq!(Err(Error::InvalidPeaksEncoding), decode_peaks(&bytes[..3]));
+
+    // truncated last peak
+    assert_eq!(
+        Err(Error::InvalidPeaksEncoding),
+        decode_peaks(&bytes[..bytes.len() - 1])
+    );
+
+    // peak count does not match the number of hashes
+    let mut bytes = bytes;
+    bytes[0] = 4;
+
+    assert_eq!(Err(Error::InvalidPeaksEncoding), decode_peaks(&bytes));
+}
@@ -15,6 +15,28 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! Merkle-Mountain-Range implementation.
+//!
+//! # Hashing
+//!
+//! All hashes are 32 byte Blake2b hashes. Node positions are `1` based, node
+//! indices are `0` based, i.e. `idx = pos - 1`. Every node hash is salted with
+//! its index using [`hash_with_index`], i.e. `H(idx.to_le_bytes() || hash)`.
+//!
+//! - a leaf `elem` at index `idx` is stored as `hash_with_index(idx, H(elem.encode()))`
+//! - a [`struct@Hash`] leaf `h` appended using [`MerkleMountainRange::append_hash`] is
+//!   stored as `hash_with_index(idx, h)`, without encoding and hashing it again
+//! - a parent at index `idx` with children `l` and `r` is stored as
+//!   `hash_with_index(idx, H(l || r))`
+//! - the root is calculated by bagging the peaks from right to left. Starting with
+//!   the rightmost peak `h`, each peak `p` to the left is folded in as
+//!   `h = hash_with_index(size, H(p || h))`
+//!
+//! A MMR with a single peak has that peak as its root. An empty MMR has
+//! [`ZERO_HASH`] as its root.
+//!
+//! Other MMR implementations, like the one used by substrate's `pallet-mmr`, do
+//! not salt node hashes with their index. Proofs are therefore not interchangeable.
+//! See [`compat`] for verifying such proofs.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -24,15 +46,39 @@ include!("std.rs");
 #[cfg(not(feature = "std"))]
 include!("no_std.rs");
 
+pub use batch::BatchProof;
+pub use cache::VerifiedProofCache;
 pub use error::{Error, Result};
-pub use hash::{hash_with_index, Hash, Hashable};
-pub use mmr::MerkleMountainRange;
-pub use proof::MerkleProof;
-pub use store::{Store, VecStore};
+pub use hash::{
+    hash_with_index, hash_with_index_be, hash_with_index_u128, leaf_hash, Hash, Hashable,
+    IndexEncoding, ZERO_HASH,
+};
+pub use header::{decode_peaks, encode_peaks, MmrHeader};
+pub use log::AppendLog;
+pub use mmr::{
+    AppendDelta, AppendInfo, AppendResult, CompactMmr, MerkleMountainRange, ValidationReport,
+    VerificationDiagnosis,
+};
+pub use proof::{FullProof, MerkleProof, ProofSummary, ProofVerifier};
+pub use store::{IndexedVecStore, PackedVecStore, Store, VecStore};
 
+#[cfg(any(test, feature = "test-util"))]
+pub use counting_store::CountingStore;
+#[cfg(feature = "sled")]
+pub use sled_store::SledStore;
+
+mod batch;
+mod cache;
+pub mod compat;
+#[cfg(any(test, feature = "test-util"))]
+mod counting_store;
 mod error;
 mod hash;
+mod header;
+mod log;
 mod mmr;
 mod proof;
+#[cfg(feature = "sled")]
+mod sled_store;
 mod store;
 mod utils;
@@ -0,0 +1,112 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Append-only log backed by a Merkle-Mountain-Range
+
+use codec::{Decode, Encode};
+
+use crate::{utils, Error, Hash, MerkleMountainRange, MerkleProof, Result, VecStore};
+
+/// An append-only log of elements, each identified by a sequence number.
+///
+/// Sequence numbers start at `0` and are increased by one for each appended element,
+/// i.e. the sequence number of an element is its MMR leaf index.
+pub struct AppendLog<T>
+where
+    T: Clone + Decode + Encode,
+{
+    mmr: MerkleMountainRange<T, VecStore<T>>,
+    len: u64,
+}
+
+impl<T> AppendLog<T>
+where
+    T: Clone + Decode + Encode,
+{
+    /// Return a new, empty log.
+    pub fn new() -> Self {
+        AppendLog {
+            mmr: MerkleMountainRange::new(0, VecStore::new()),
+            len: 0,
+        }
+    }
+
+    /// Append `elem` to the log. Return the sequence number of `elem`.
+    #[must_use = "appending may fail and leave the log unchanged"]
+    pub fn append(&mut self, elem: &T) -> Result<u64> {
+        self.mmr.append(elem)?;
+
+        let seq = self.len;
+        self.len += 1;
+
+        Ok(seq)
+    }
+
+    /// Return the element with sequence number `seq`, if any.
+    pub fn get(&self, seq: u64) -> Option<T> {
+        self.mmr
+            .store()
+            .data
+            .as_ref()
+            .and_then(|data| data.get(seq as usize))
+            .cloned()
+    }
+
+    /// Return a MMR membership proof for the element with sequence number `seq`.
+    ///
+    /// [`Error::LeafIndexOutOfRange`] is returned, if there is no such element.
+    pub fn proof(&self, seq: u64) -> Result<MerkleProof> {
+        if seq >= self.len {
+            return Err(Error::LeafIndexOutOfRange(seq, self.len));
+        }
+
+        self.mmr.proof(Self::position(seq))
+    }
+
+    /// Return the MMR leaf position of the element with sequence number `seq`.
+    pub fn position(seq: u64) -> u64 {
+        utils::mmr_size(seq) + 1
+    }
+
+    /// Return the MMR root of the log.
+    pub fn root(&self) -> Result<Hash> {
+        self.mmr.root()
+    }
+
+    /// Return the number of elements in the log.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Return `true`, if the log does not contain any elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return a reference to the underlying MMR.
+    pub fn mmr(&self) -> &MerkleMountainRange<T, VecStore<T>> {
+        &self.mmr
+    }
+}
+
+impl<T> Default for AppendLog<T>
+where
+    T: Clone + Decode + Encode,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
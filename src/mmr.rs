@@ -16,16 +16,164 @@
 
 use core::marker::PhantomData;
 
-use codec::{Decode, Encode};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use codec::{Decode, DecodeAll, Encode, Input, Output};
+use scale_info::TypeInfo;
 
 use crate::{
-    hash::ZERO_HASH, hash_with_index, utils, Error, Hash, Hashable, MerkleProof, Result, Store,
+    batch, hash::ZERO_HASH, store::OverlayStore, utils, BatchProof, Error, Hash, Hashable,
+    IndexEncoding, MerkleProof, MmrHeader, Result, Store, VecStore,
 };
 
 #[cfg(test)]
 #[path = "mmr_tests.rs"]
 mod tests;
 
+/// The nodes created by appending a single element to a MMR.
+///
+/// A delta allows a replica to apply the very same append, checking the delta hashes
+/// against its own MMR, see [`MerkleMountainRange::apply_delta`].
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct AppendDelta<T> {
+    /// appended element
+    pub elem: T,
+    /// position of the first new node, i.e. the leaf
+    pub pos: u64,
+    /// hashes of all new nodes at consecutive positions, starting with the leaf
+    pub hashes: Vec<Hash>,
+}
+
+/// The result of appending a single element to a MMR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AppendResult {
+    /// new MMR size
+    pub size: u64,
+    /// position of the new leaf
+    pub leaf_pos: u64,
+    /// `0` based index of the new leaf among all leaves, not to be confused with
+    /// the store index `leaf_pos - 1`
+    pub leaf_index: u64,
+}
+
+/// Information about the nodes added by appending a single element to a MMR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AppendInfo {
+    /// new MMR size
+    pub size: u64,
+    /// number of new nodes, i.e. the new leaf plus any new parents
+    pub new_nodes: u64,
+    /// number of peaks merged into a new parent, i.e. `new_nodes - 1`
+    pub merges: u64,
+}
+
+/// The minimal state to resume appending to a MMR, see [`MerkleMountainRange::compact`].
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct CompactMmr {
+    /// MMR size, i.e. total number of nodes
+    pub size: u64,
+    /// MMR peak hashes, left to right
+    pub peaks: Vec<Hash>,
+}
+
+impl CompactMmr {
+    /// Return a MMR resumed from the compacted state on top of `store`, ready for
+    /// further appends.
+    ///
+    /// `store` has to contain the compacted MMR, i.e. its length has to match the MMR
+    /// size and it has to return the compacted peaks. Use [`CompactMmr::peak_store`] for
+    /// a [`VecStore`] retaining just the peaks.
+    ///
+    /// An error is returned, if the size is unstable, the number of peaks does not
+    /// match the size, or if `store` does not match the compacted state.
+    pub fn resume<T, S>(self, store: S) -> Result<MerkleMountainRange<T, S>>
+    where
+        T: Clone + Decode + Encode,
+        S: Store<T>,
+    {
+        utils::check_size(self.size)?;
+
+        let expected = utils::expected_peak_count(self.size);
+
+        if self.peaks.len() != expected {
+            return Err(Error::InvalidPeakCount(
+                self.peaks.len() as u64,
+                expected as u64,
+            ));
+        }
+
+        let store_len = store.len();
+
+        if store_len != self.size {
+            return Err(Error::StoreDesync(self.size, store_len));
+        }
+
+        let stored = store.peak_hashes(self.size)?;
+        let peaks = utils::peaks(self.size);
+
+        for ((pos, stored), want) in peaks.into_iter().zip(stored).zip(self.peaks) {
+            if stored != want {
+                return Err(Error::InvalidNodeHash(pos - 1, stored, want));
+            }
+        }
+
+        Ok(MerkleMountainRange::new(self.size, store))
+    }
+
+    /// Return a [`VecStore`] retaining the compacted peaks only, like after
+    /// [`MerkleMountainRange::prune_to_peaks`]. Elements are not retained.
+    pub fn peak_store<T>(&self) -> VecStore<T> {
+        VecStore {
+            data: None,
+            hashes: Vec::new(),
+            pruned: self.size,
+            retained: utils::peaks(self.size)
+                .into_iter()
+                .map(|p| p - 1)
+                .zip(self.peaks.iter().copied())
+                .collect(),
+        }
+    }
+}
+
+/// The result of validating a MMR, see [`MerkleMountainRange::validate_report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// total number of nodes checked, i.e. the MMR size
+    pub checked_nodes: u64,
+    /// number of inner nodes, whose hash has been re-calculated
+    pub inner_nodes: u64,
+    /// position of the first invalid inner node, if any
+    pub first_invalid: Option<u64>,
+    /// positions of all invalid inner nodes in ascending order
+    pub all_invalid: Vec<u64>,
+}
+
+/// The reason a proof does not verify against a MMR, see
+/// [`MerkleMountainRange::explain_verification_failure`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationDiagnosis {
+    /// The proof verifies, there is no failure to explain
+    Valid,
+    /// The position is out of range or not a leaf position
+    InvalidPosition(u64),
+    /// The leaf hash of the element does not match the stored leaf hash, i.e. the
+    /// element is not the leaf at this position. Contains both, the element leaf hash
+    /// and the stored leaf hash.
+    LeafHashMismatch(Hash, Hash),
+    /// The proof is for a different MMR size, i.e. the peaks changed. Contains both,
+    /// the proof MMR size and the current MMR size.
+    SizeMismatch(u64, u64),
+    /// The proof path is too short. Contains the path length and the expected length.
+    PathTooShort(usize, usize),
+    /// The proof path is too long. Contains the path length and the expected length.
+    PathTooLong(usize, usize),
+    /// The proof path hashes are wrong. Contains both, the root implied by the proof
+    /// and the MMR root.
+    RootMismatch(Hash, Hash),
+    /// The MMR store failed to provide a node, e.g. because it has been pruned
+    StoreError(Error),
+}
+
 /// Merkle-Mountain-Range (MMR) implementation.
 ///
 /// All tree positions start at `'1'`. MMR positions are depth-frist, post-order tree
@@ -45,10 +193,57 @@ where
     size: u64,
     // backing store for the MMR
     store: S,
+    // record a root every `checkpoint_interval` leaves, if set
+    checkpoint_interval: Option<u64>,
+    // recorded roots as `(leaf_count, root)` tuples, ordered by leaf count
+    checkpoints: Vec<(u64, Hash)>,
+    // byte order of the node index mixed into node hashes
+    index_encoding: IndexEncoding,
+    // validate all new nodes after each append, if set
+    paranoid: bool,
+    // reject elements with an empty SCALE encoding, if set
+    reject_empty_leaves: bool,
     // make rustc happy
     _marker: PhantomData<T>,
 }
 
+/// Encode the MMR size followed by the store.
+///
+/// Checkpoints are not part of the encoding.
+impl<T, S> Encode for MerkleMountainRange<T, S>
+where
+    T: Clone + Decode + Encode,
+    S: Store<T> + Encode,
+{
+    fn size_hint(&self) -> usize {
+        self.size.size_hint() + self.store.size_hint()
+    }
+
+    fn encode_to<W: Output + ?Sized>(&self, dest: &mut W) {
+        self.size.encode_to(dest);
+        self.store.encode_to(dest);
+    }
+}
+
+/// Decode the MMR size followed by the store, rejecting a size which does not match
+/// the store length.
+impl<T, S> Decode for MerkleMountainRange<T, S>
+where
+    T: Clone + Decode + Encode,
+    S: Store<T> + Decode,
+{
+    fn decode<I: Input>(input: &mut I) -> core::result::Result<Self, codec::Error> {
+        let size = u64::decode(input)?;
+        let store = S::decode(input)?;
+
+        if store.len() != size {
+            return Err("MMR size does not match the store length".into());
+        }
+
+        Ok(MerkleMountainRange::new(size, store))
+    }
+}
+
 impl<T, S> MerkleMountainRange<T, S>
 where
     T: Clone + Decode + Encode,
@@ -58,14 +253,141 @@ where
         MerkleMountainRange {
             size,
             store,
+            checkpoint_interval: None,
+            checkpoints: Vec::new(),
+            index_encoding: IndexEncoding::default(),
+            paranoid: false,
+            reject_empty_leaves: false,
             _marker: PhantomData,
         }
     }
 
+    /// Return the MMR, recording a root checkpoint every `interval` leaves.
+    ///
+    /// Checkpoints are recorded while appending and kept in memory only, i.e. they
+    /// are not persisted in the store.
+    pub fn with_checkpoint_interval(mut self, interval: u64) -> Self {
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+
+    /// Return the MMR, mixing node indices into node hashes using `encoding`.
+    ///
+    /// The encoding is not persisted, i.e. it has to be set again for a MMR decoded
+    /// or otherwise created from an existing store. Proofs for a MMR using big-endian
+    /// indices have to be verified with [`MerkleProof::verify_with_index_encoding`].
+    pub fn with_index_encoding(mut self, encoding: IndexEncoding) -> Self {
+        self.index_encoding = encoding;
+        self
+    }
+
+    /// Return the MMR, validating all new nodes against the store after each append,
+    /// if `paranoid` is set.
+    ///
+    /// All new nodes, including the leaf, are read back from the store and compared
+    /// with the calculated hashes, before the MMR size is increased. This catches store
+    /// bugs right away, at the cost of re-reading all new nodes from the store. Paranoid
+    /// mode is off by default.
+    ///
+    /// On a mismatch, [`Error::InvalidNodeHash`] is returned and the MMR is not extended.
+    /// The bogus nodes are removed from the store again using [`Store::undo_append`], so
+    /// that the append can be retried. If the store does not support this, it is left
+    /// out of sync and any further append fails with [`Error::StoreDesync`].
+    pub fn with_paranoid(mut self, paranoid: bool) -> Self {
+        self.paranoid = paranoid;
+        self
+    }
+
+    /// Return the MMR, rejecting elements with an empty SCALE encoding on append with
+    /// [`Error::EmptyLeaf`], if `reject` is set.
+    ///
+    /// Such elements are likely a caller bug, e.g. a unit `()` element. Elements with a
+    /// non-empty encoding are always accepted, e.g. `false`, `0u8` or an empty `Vec<u8>`,
+    /// which is encoded as its compact length prefix. Rejecting empty leaves is off by
+    /// default.
+    pub fn with_reject_empty_leaves(mut self, reject: bool) -> Self {
+        self.reject_empty_leaves = reject;
+        self
+    }
+
+    /// Return a new MMR, rebuilt by appending all `leaves` to `store`.
+    ///
+    /// This allows to recover from a corrupted store, given the original leaves are
+    /// still available. `store` is expected to be empty, otherwise
+    /// [`Error::StoreDesync`] is returned.
+    pub fn rebuild_from_leaves(leaves: &[T], store: S) -> Result<Self> {
+        let mut mmr = MerkleMountainRange::new(0, store);
+
+        for leaf in leaves {
+            mmr.append(leaf)?;
+        }
+
+        Ok(mmr)
+    }
+
     /// Append `elem` to the MMR. Return new MMR size.
+    ///
+    /// An error is returned, if the number of nodes in the store does not match the
+    /// MMR size. Appending to such a store would silently corrupt the MMR.
+    ///
+    /// All new node hashes are calculated upfront and handed over to the store with a
+    /// single [`Store::append`]. The MMR size is increased only after the store append
+    /// succeeded. Given a store honouring the atomicity contract of [`Store::append`],
+    /// a failed append leaves both, the MMR and the store, unchanged and can be retried.
+    #[must_use = "appending may fail and leave the MMR unchanged"]
     pub fn append(&mut self, elem: &T) -> Result<u64> {
+        self.append_hashed(elem, elem.encode().hash())
+    }
+
+    /// Append `elem` to the MMR. Return the new MMR size together with the position
+    /// and leaf index of the new leaf.
+    #[must_use = "appending may fail and leave the MMR unchanged"]
+    pub fn try_append(&mut self, elem: &T) -> Result<AppendResult> {
+        let leaf_pos = self.size + 1;
+        let leaf_index = utils::leaf_count(self.size).ok_or(Error::UnstableSize(self.size))?;
+
+        let size = self.append(elem)?;
+
+        Ok(AppendResult {
+            size,
+            leaf_pos,
+            leaf_index,
+        })
+    }
+
+    /// Append `elem` to the MMR. Return the new MMR size together with the number of
+    /// new nodes and peak merges.
+    #[must_use = "appending may fail and leave the MMR unchanged"]
+    pub fn append_info(&mut self, elem: &T) -> Result<AppendInfo> {
+        let old_size = self.size;
+        let size = self.append(elem)?;
+        let new_nodes = size - old_size;
+
+        Ok(AppendInfo {
+            size,
+            new_nodes,
+            merges: new_nodes - 1,
+        })
+    }
+
+    /// Append the element with SCALE encoding `encoded` to the MMR. Return new MMR size.
+    ///
+    /// This avoids encoding the element again, if it is already available in encoded
+    /// form. The leaf is hashed from `encoded` as is, while the element passed to the
+    /// store is decoded from it. [`Error::InvalidLeafEncoding`] is returned, if `encoded`
+    /// can not be decoded into an element.
+    #[must_use = "appending may fail and leave the MMR unchanged"]
+    pub fn append_encoded(&mut self, encoded: &[u8]) -> Result<u64> {
+        let elem = T::decode_all(&mut &encoded[..]).map_err(|_| Error::InvalidLeafEncoding)?;
+
+        self.append_hashed(&elem, encoded.to_vec().hash())
+    }
+
+    fn append_hashed(&mut self, elem: &T, elem_hash: Hash) -> Result<u64> {
+        self.check_append(elem)?;
+
         let idx = self.size;
-        let node_hash = hash_with_index(idx, &elem.encode().hash());
+        let node_hash = self.index_encoding.hash_with_index(idx, &elem_hash);
 
         let (peak_map, node_height) = utils::peak_height_map(idx);
 
@@ -74,46 +396,382 @@ where
             return Err(Error::InvalidNodeHeight(node_height));
         }
 
-        let (new, peak_hashes) = self.bag_the_peaks(node_hash, peak_map)?;
+        let (_, peak_hashes) = self.bag_the_peaks(node_hash, peak_map)?;
+
+        self.commit_append(elem, &peak_hashes)
+    }
+
+    /// Check that `elem` can be appended to the MMR.
+    fn check_append(&self, elem: &T) -> Result<()> {
+        if self.reject_empty_leaves && elem.encoded_size() == 0 {
+            return Err(Error::EmptyLeaf);
+        }
+
+        let store_len = self.store.len();
+
+        if store_len != self.size {
+            return Err(Error::StoreDesync(self.size, store_len));
+        }
+
+        Ok(())
+    }
+
+    /// Append `elem` together with all new node `hashes` to the store and extend the
+    /// MMR accordingly. Return the new MMR size.
+    ///
+    /// This is the single place where the MMR is extended, [`MerkleMountainRange::check_append`]
+    /// is expected to be called before.
+    fn commit_append(&mut self, elem: &T, hashes: &[Hash]) -> Result<u64> {
+        let idx = self.size;
+        let size = idx + hashes.len() as u64;
+
+        // calculate the checkpoint up front, so a failure leaves the MMR unchanged
+        let checkpoint = match self.checkpoint_interval {
+            Some(interval) => {
+                let leaves = utils::leaf_count(size).unwrap_or_default();
+
+                if leaves.is_multiple_of(interval) {
+                    Some((leaves, self.projected_root(hashes)?))
+                } else {
+                    None
+                }
+            }
+            None => None,
+        };
+
+        self.store.append(elem, hashes)?;
+
+        if self.paranoid {
+            if let Err(err) = self.validate_appended(idx, hashes) {
+                // keep the store in sync with the MMR size, if possible
+                let _ = self.store.undo_append(hashes.len() as u64);
+                return Err(err);
+            }
+        }
 
-        self.store.append(elem, &peak_hashes)?;
-        self.size += new;
+        self.size = size;
+        self.checkpoints.extend(checkpoint);
 
         Ok(self.size)
     }
 
+    /// Return the root hash the MMR will have once the new node `hashes`, as returned
+    /// by [`MerkleMountainRange::bag_the_peaks`], have been appended.
+    ///
+    /// Every new node but the leaf merges the rightmost peak, the last new node is
+    /// the new rightmost peak.
+    fn projected_root(&self, hashes: &[Hash]) -> Result<Hash> {
+        let mut peaks = if self.size == 0 {
+            vec![]
+        } else {
+            self.peaks()?
+        };
+
+        let merged = hashes.len().saturating_sub(1);
+        peaks.truncate(peaks.len().saturating_sub(merged));
+        peaks.extend(hashes.last());
+
+        let size = self.size + hashes.len() as u64;
+
+        batch::bag_peaks(size, &peaks, self.index_encoding).ok_or(Error::MissingRootNode)
+    }
+
+    /// Return the root checkpoint recorded at `leaf_count` leaves, if any.
+    ///
+    /// See [`MerkleMountainRange::with_checkpoint_interval`].
+    pub fn checkpoint_root(&self, leaf_count: u64) -> Option<Hash> {
+        self.checkpoints
+            .binary_search_by_key(&leaf_count, |&(leaves, _)| leaves)
+            .ok()
+            .map(|i| self.checkpoints[i].1)
+    }
+
+    /// Append `elem` to the MMR. Return the new MMR size together with the delta of
+    /// all new nodes.
+    pub fn append_delta(&mut self, elem: &T) -> Result<(u64, AppendDelta<T>)> {
+        let pos = self.size + 1;
+
+        self.append(elem)?;
+
+        let indices = (pos - 1..self.size).collect::<Vec<_>>();
+        let hashes = self.store.get_many(&indices)?;
+
+        Ok((
+            self.size,
+            AppendDelta {
+                elem: elem.clone(),
+                pos,
+                hashes,
+            },
+        ))
+    }
+
+    /// Apply `delta`, as returned by [`MerkleMountainRange::append_delta`], to the MMR.
+    /// Return the new MMR size.
+    ///
+    /// [`Error::InvalidDelta`] is returned, if the position does not match and
+    /// [`Error::InvalidDeltaLength`], if the number of hashes does not match. The hashes
+    /// in `delta` are checked against the hashes appending `delta.elem` creates, i.e.
+    /// the leaf hash as well as all new parents up to the new peak. The first mismatch
+    /// is returned as [`Error::InvalidNodeHash`].
+    ///
+    /// Otherwise, the delta is appended like [`MerkleMountainRange::append`] does, i.e.
+    /// subject to the same checks and recording checkpoints.
+    pub fn apply_delta(&mut self, delta: &AppendDelta<T>) -> Result<u64> {
+        if delta.pos != self.size + 1 {
+            return Err(Error::InvalidDelta(delta.pos, self.size + 1));
+        }
+
+        let (peak_map, node_height) = utils::peak_height_map(self.size);
+
+        // a new node always has to be a leave node (height = 0)
+        if node_height != 0 {
+            return Err(Error::InvalidNodeHeight(node_height));
+        }
+
+        // the new leaf plus a new parent for each merged peak
+        let new = 1 + peak_map.trailing_ones() as u64;
+
+        if delta.hashes.len() as u64 != new {
+            return Err(Error::InvalidDeltaLength(delta.hashes.len() as u64, new));
+        }
+
+        self.check_append(&delta.elem)?;
+
+        let leaf_hash = self
+            .index_encoding
+            .hash_with_index(self.size, &delta.elem.encode().hash());
+        let (_, hashes) = self.bag_the_peaks(leaf_hash, peak_map)?;
+
+        for (idx, (got, want)) in (self.size..).zip(delta.hashes.iter().zip(hashes.iter())) {
+            if got != want {
+                return Err(Error::InvalidNodeHash(idx, *got, *want));
+            }
+        }
+
+        self.commit_append(&delta.elem, &delta.hashes)
+    }
+
+    /// Append all `elems` to the MMR. Return the new MMR size together with a batch
+    /// proof for exactly the appended leaves.
+    pub fn append_batch_proof(&mut self, elems: &[T]) -> Result<(u64, BatchProof)> {
+        let mut positions = Vec::with_capacity(elems.len());
+
+        for elem in elems {
+            positions.push(self.size + 1);
+            self.append(elem)?;
+        }
+
+        Ok((self.size, self.batch_proof(&positions)?))
+    }
+
+    /// Append all elements of `iter` to the MMR. Return the leaf position of each
+    /// appended element, in order.
+    ///
+    /// Elements appended before an error remain in the MMR.
+    #[must_use = "appending may fail and leave the MMR partially extended"]
+    pub fn append_all_tracked<I>(&mut self, iter: I) -> Result<Vec<u64>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        iter.into_iter()
+            .map(|elem| {
+                let pos = self.size + 1;
+                self.append(&elem).map(|_| pos)
+            })
+            .collect()
+    }
+
+    /// Reserve store capacity for at least `additional_leaves` more leaves.
+    ///
+    /// The number of additional nodes, i.e. leaves and parents, is calculated from the
+    /// current MMR size and handed over to [`Store::reserve`] along with the number of
+    /// additional leaves. Reserving upfront avoids repeated store reallocations while
+    /// appending.
+    pub fn reserve(&mut self, additional_leaves: u64) {
+        let leaves = utils::leaf_count(self.size).unwrap_or_default();
+        let size = utils::mmr_size(leaves.saturating_add(additional_leaves));
+
+        self.store
+            .reserve(additional_leaves, size.saturating_sub(self.size));
+    }
+
+    /// Prune all nodes from the store, except the current peaks.
+    ///
+    /// The peaks are sufficient to calculate the MMR root and to append further
+    /// elements. Proofs for leaves below a pruned node will fail with [`Error::Pruned`],
+    /// so will [`MerkleMountainRange::validate`].
+    pub fn prune_to_peaks(&mut self) -> Result<()> {
+        utils::check_size(self.size)?;
+
+        let peaks = utils::peaks(self.size)
+            .into_iter()
+            .map(|p| p - 1)
+            .collect::<Vec<_>>();
+
+        self.store.prune(&peaks)
+    }
+
+    /// Release excess store capacity, typically after [`MerkleMountainRange::prune_to_peaks`].
+    pub fn shrink_to_fit(&mut self) {
+        self.store.shrink_to_fit();
+    }
+
     /// Validate the MMR by re-calculating the hash of all inner, i.e. parent nodes.
     /// Retrun `true`, if the MMR is valid or an error.
+    #[must_use = "an invalid MMR is reported as an error"]
     pub fn validate(&self) -> Result<bool> {
-        for pos in 1..=self.size {
-            let height = utils::node_height(pos.saturating_sub(1));
+        for pos in utils::inner_positions(self.size) {
+            self.validate_parent(pos - 1)?;
+        }
 
-            // inner nodes, i.e. parents start at height 1
-            if height > 0 {
-                let idx = pos - 1u64;
+        Ok(true)
+    }
 
-                // recalculate parent hash
-                let left_idx = idx - (1 << height);
-                let left_hash = self.store.hash_at(left_idx)?;
+    /// Validate the MMR like [`MerkleMountainRange::validate`] does and return the MMR
+    /// root on success.
+    ///
+    /// The first validation error is returned as is, without calculating the root.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_self(&self) -> Result<Hash> {
+        self.validate()?;
+        self.root()
+    }
 
-                let right_idx = idx - 1;
-                let right_hash = self.store.hash_at(right_idx)?;
+    /// Explain why `proof` fails to verify that `elem` is the leaf at position `pos`
+    /// given the current MMR root.
+    ///
+    /// Like for [`MerkleProof::verify_typed`], the leaf data hash is [`Hashable::hash`]
+    /// of `elem`. Pass the SCALE encoding of an element to match the leaf hash of
+    /// [`MerkleMountainRange::append`].
+    ///
+    /// Checks are done in the order of the [`VerificationDiagnosis`] variants and the
+    /// first failing check is returned. [`VerificationDiagnosis::Valid`] is returned,
+    /// if the proof verifies.
+    pub fn explain_verification_failure(
+        &self,
+        proof: &MerkleProof,
+        elem: &dyn Hashable,
+        pos: u64,
+    ) -> VerificationDiagnosis {
+        if utils::check_pos(pos, self.size).is_err() || !utils::is_leaf(pos - 1) {
+            return VerificationDiagnosis::InvalidPosition(pos);
+        }
 
-                let tmp = (left_hash, right_hash).hash();
-                let tmp = hash_with_index(idx, &tmp);
+        let leaf_hash = self.index_encoding.hash_with_index(pos - 1, &elem.hash());
 
-                // check against expected parent hash
-                let parent_hash = self.store.hash_at(idx)?;
+        let stored = match self.hash(pos) {
+            Ok(h) => h,
+            Err(e) => return VerificationDiagnosis::StoreError(e),
+        };
 
-                if tmp != parent_hash {
-                    return Err(Error::InvalidNodeHash(idx, parent_hash, tmp));
-                }
-            }
+        if leaf_hash != stored {
+            return VerificationDiagnosis::LeafHashMismatch(leaf_hash, stored);
+        }
+
+        if proof.mmr_size != self.size {
+            return VerificationDiagnosis::SizeMismatch(proof.mmr_size, self.size);
+        }
+
+        let expected = utils::proof_len(pos, self.size);
+
+        if proof.path.len() < expected {
+            return VerificationDiagnosis::PathTooShort(proof.path.len(), expected);
+        }
+
+        if proof.path.len() > expected {
+            return VerificationDiagnosis::PathTooLong(proof.path.len(), expected);
+        }
+
+        let root = match self.root() {
+            Ok(h) => h,
+            Err(e) => return VerificationDiagnosis::StoreError(e),
+        };
+
+        let peaks = utils::peaks(self.size);
+        let got = proof.fold_root(leaf_hash, pos, &peaks, self.index_encoding);
+
+        if got != root {
+            return VerificationDiagnosis::RootMismatch(got, root);
+        }
+
+        VerificationDiagnosis::Valid
+    }
+
+    /// Validate all inner nodes in the inclusive position range `start_pos..=end_pos`
+    /// like [`MerkleMountainRange::validate`] does.
+    #[must_use = "an invalid MMR is reported as an error"]
+    pub fn validate_range(&self, start_pos: u64, end_pos: u64) -> Result<bool> {
+        utils::nodes_between(start_pos, end_pos, self.size)?;
+
+        for pos in (start_pos..=end_pos).filter(|&pos| utils::height_at_pos(pos) > 0) {
+            self.validate_parent(pos - 1)?;
         }
 
         Ok(true)
     }
 
+    /// Validate the MMR like [`MerkleMountainRange::validate`], but without stopping at
+    /// the first invalid node.
+    ///
+    /// Inner nodes which can not be re-calculated, e.g. because of a pruned child, are
+    /// reported as invalid as well.
+    pub fn validate_report(&self) -> ValidationReport {
+        let mut report = ValidationReport {
+            checked_nodes: self.size,
+            ..Default::default()
+        };
+
+        for pos in utils::inner_positions(self.size) {
+            report.inner_nodes += 1;
+
+            if self.validate_parent(pos - 1).is_err() {
+                report.first_invalid.get_or_insert(pos);
+                report.all_invalid.push(pos);
+            }
+        }
+
+        report
+    }
+
+    /// Validate that the store contains the node `hashes` starting at index `idx`.
+    fn validate_appended(&self, idx: u64, hashes: &[Hash]) -> Result<()> {
+        let indices = (idx..idx + hashes.len() as u64).collect::<Vec<_>>();
+        let stored = self.store.get_many(&indices)?;
+
+        for ((idx, stored), want) in indices.into_iter().zip(stored).zip(hashes) {
+            if stored != *want {
+                return Err(Error::InvalidNodeHash(idx, stored, *want));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Re-calculate the hash of the parent node at index `idx` and compare it with
+    /// the stored one.
+    fn validate_parent(&self, idx: u64) -> Result<()> {
+        let height = utils::node_height(idx);
+
+        let left_idx = idx - (1 << height);
+        let left_hash = self.store.hash_at(left_idx)?;
+
+        let right_idx = idx - 1;
+        let right_hash = self.store.hash_at(right_idx)?;
+
+        let tmp = (left_hash, right_hash).hash();
+        let tmp = self.index_encoding.hash_with_index(idx, &tmp);
+
+        // check against expected parent hash
+        let parent_hash = self.store.hash_at(idx)?;
+
+        if tmp != parent_hash {
+            return Err(Error::InvalidNodeHash(idx, parent_hash, tmp));
+        }
+
+        Ok(())
+    }
+
     /// Return a MMR membership proof for a leaf node at position `pos`.
     ///
     /// Note that this proof is `complete` in the sense that it does include all
@@ -121,27 +779,80 @@ where
     ///
     /// See [`partial_proof()`] for a proof containing only a subset of the nodes.
     ///
+    #[must_use = "generating a proof may fail"]
     pub fn proof(&self, pos: u64) -> Result<MerkleProof> {
+        self.leaf_proof(pos, true)
+    }
+
+    /// Return a MMR membership proof for a leaf at position `pos`, like
+    /// [`MerkleMountainRange::proof`] does, but without reading the leaf hash.
+    ///
+    /// The caller is trusted to know that the leaf exists. Only the proof nodes
+    /// are read from the store.
+    #[must_use = "generating a proof may fail"]
+    pub fn proof_path_only(&self, pos: u64) -> Result<MerkleProof> {
+        self.leaf_proof(pos, false)
+    }
+
+    /// Return a MMR membership proof for a leaf at position `pos` up to the peak the
+    /// leaf belongs to.
+    ///
+    /// The proof path contains the siblings along the family path only, i.e. no peak
+    /// bagging hashes. Use [`MerkleProof::verify_within_peak`] to verify the proof
+    /// against a trusted peak hash.
+    #[must_use = "generating a proof may fail"]
+    pub fn proof_within_peak(&self, pos: u64) -> Result<MerkleProof> {
+        utils::check_size(self.size)?;
+        utils::check_pos(pos, self.size)?;
+
         if !utils::is_leaf(pos.saturating_sub(1)) {
             return Err(Error::ExpectingLeafNode(pos));
         }
 
-        self.hash(pos)?;
+        // fetch the leaf, to make sure it exists, and all siblings at once
+        let indices = core::iter::once(pos)
+            .chain(utils::family_path(pos, self.size).into_iter().map(|n| n.1))
+            .map(|p| p - 1)
+            .collect::<Vec<_>>();
+
+        let hashes = self.store.get_many(&indices)?;
+
+        Ok(MerkleProof {
+            mmr_size: self.size,
+            path: hashes[1..].to_vec(),
+        })
+    }
+
+    fn leaf_proof(&self, pos: u64, read_leaf: bool) -> Result<MerkleProof> {
+        utils::check_size(self.size)?;
+        utils::check_pos(pos, self.size)?;
+
+        if !utils::is_leaf(pos.saturating_sub(1)) {
+            return Err(Error::ExpectingLeafNode(pos));
+        }
 
         let family_path = utils::family_path(pos, self.size);
+        let peak = family_path.last().map_or(pos, |n| n.0);
+        let lower = utils::peaks(self.size)
+            .into_iter()
+            .filter(|&p| p > peak)
+            .count();
 
-        let mut path = family_path
-            .iter()
-            .filter_map(|x| self.hash(x.1).ok())
+        // fetch the leaf, to make sure it exists, and all proof nodes at once
+        let leaf = Some(pos).filter(|_| read_leaf);
+        let indices = leaf
+            .into_iter()
+            .chain(utils::proof_positions(pos, self.size))
+            .map(|p| p - 1)
             .collect::<Vec<_>>();
 
-        let peak = if let Some(n) = family_path.last() {
-            n.0
-        } else {
-            pos
-        };
+        let hashes = self.store.get_many(&indices)?;
+        let (siblings, peaks) = hashes[usize::from(read_leaf)..].split_at(family_path.len());
+        let (lower, higher) = peaks.split_at(lower);
 
-        path.append(&mut self.peak_path(peak));
+        let mut path = siblings.to_vec();
+        path.extend(batch::bag_peaks(self.size, lower, self.index_encoding));
+        path.extend_from_slice(higher);
 
         Ok(MerkleProof {
             mmr_size: self.size,
@@ -154,10 +865,20 @@ where
     /// Note that this is a `partial` proof in the sense that it does **not** include all
     /// nodes. Only a subset of nodes for a MMR with the given size is included.
     ///
+    /// `size` has to be a stable, historical MMR size, i.e. not exceeding the current size.
+    ///
     /// See [`proof()`] for a complete proof.
     ///
     pub fn partial_proof(&self, pos: u64, size: u64) -> Result<MerkleProof> {
-        if !utils::is_leaf(pos) {
+        if size > self.size {
+            return Err(Error::FutureSize(size, self.size));
+        }
+
+        utils::check_size(size)?;
+
+        utils::check_pos(pos, size)?;
+
+        if !utils::is_leaf(pos - 1) {
             return Err(Error::ExpectingLeafNode(pos));
         }
 
@@ -176,9 +897,7 @@ where
             pos
         };
 
-        if peak < size {
-            path.append(&mut self.peak_path(peak));
-        }
+        path.append(&mut self.peak_path(peak, size));
 
         Ok(MerkleProof {
             mmr_size: size,
@@ -186,56 +905,260 @@ where
         })
     }
 
+    /// Return a MMR membership proof for a leaf at position `pos`, which will be valid
+    /// once all `future_leaves` have been appended.
+    ///
+    /// Appending `future_leaves` is simulated, the MMR itself is not changed.
+    pub fn projected_proof(&self, pos: u64, future_leaves: &[T]) -> Result<MerkleProof> {
+        let store = OverlayStore::new(&self.store);
+        let mut mmr = MerkleMountainRange::<T, _>::new(self.size, store)
+            .with_index_encoding(self.index_encoding)
+            .with_reject_empty_leaves(self.reject_empty_leaves);
+
+        for leaf in future_leaves {
+            mmr.append(leaf)?;
+        }
+
+        mmr.proof(pos)
+    }
+
+    /// Return a MMR membership proof for all leaves at `positions`.
+    ///
+    /// Leaf positions have to be in strictly ascending order.
+    pub fn batch_proof(&self, positions: &[u64]) -> Result<BatchProof> {
+        let leaves = positions
+            .iter()
+            .map(|&pos| Ok((pos, self.hash(pos)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut path = vec![];
+
+        batch::fold_peaks(self.size, &leaves, self.index_encoding, |pos| {
+            let hash = self.hash(pos)?;
+            path.push(hash);
+            Ok(hash)
+        })?;
+
+        Ok(BatchProof {
+            mmr_size: self.size,
+            path,
+        })
+    }
+
+    /// Return a MMR membership proof for all leaves with `leaf_indices`.
+    ///
+    /// Leaf indices are `0` based leaf ordinals, i.e. the `n`-th appended leaf has index
+    /// `n - 1`. They have to be in strictly ascending order. [`Error::LeafIndexOutOfRange`]
+    /// is returned for a leaf index exceeding the number of leaves.
+    pub fn prove_many_leaf_indices(&self, leaf_indices: &[u64]) -> Result<BatchProof> {
+        let leaves = utils::leaf_count(self.size).ok_or(Error::UnstableSize(self.size))?;
+
+        let positions = leaf_indices
+            .iter()
+            .map(|&idx| {
+                if idx >= leaves {
+                    return Err(Error::LeafIndexOutOfRange(idx, leaves));
+                }
+
+                Ok(utils::mmr_size(idx) + 1)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.batch_proof(&positions)
+    }
+
     /// Return node hash at `pos`.
     ///
-    /// Note that in case of an error, [`Error::Store`] is returned and the error
-    /// message is referring to `pss - 1`, i.e. an index.
+    /// Note that in case of a store error, the error message is referring to
+    /// `pos - 1`, i.e. an index.
     pub fn hash(&self, pos: u64) -> Result<Hash> {
-        self.store.hash_at(pos.saturating_sub(1))
+        utils::check_pos(pos, self.size)?;
+
+        self.store.hash_at(pos - 1)
+    }
+
+    /// Return all leaf elements in append order.
+    ///
+    /// [`Error::DataNotRetained`] is returned, if the store does not retain elements.
+    pub fn leaf_data_all(&self) -> Result<Vec<T>> {
+        self.store.leaf_data()
+    }
+
+    /// Return the positions of all inner, i.e. parent nodes in ascending order.
+    pub fn inner_positions(&self) -> impl Iterator<Item = u64> {
+        utils::inner_positions(self.size)
+    }
+
+    /// Return the positions of all ancestors of the node at position `pos` up to its
+    /// peak, i.e. the parent positions along the family path, in ascending order.
+    pub fn ancestors(&self, pos: u64) -> Result<Vec<u64>> {
+        utils::check_pos(pos, self.size)?;
+
+        Ok(utils::ancestors(pos, self.size))
+    }
+
+    /// Return the number of nodes in the inclusive position range `start_pos..=end_pos`.
+    pub fn nodes_between(&self, start_pos: u64, end_pos: u64) -> Result<u64> {
+        utils::nodes_between(start_pos, end_pos, self.size)
+    }
+
+    /// Return the positions of all leaves below the peak at position `peak_pos`.
+    ///
+    /// Leaf positions are returned in ascending order. An error is returned, if
+    /// `peak_pos` is not a position of one of the current MMR peaks.
+    pub fn leaves_under_peak(&self, peak_pos: u64) -> Result<Vec<u64>> {
+        if !utils::peaks(self.size).contains(&peak_pos) {
+            return Err(Error::InvalidPeakPosition(peak_pos));
+        }
+
+        let height = utils::height_at_pos(peak_pos);
+        // a peak at `height` is the root of a subtree with `2^(height + 1) - 1` nodes
+        let first = peak_pos + 2 - (2 << height);
+
+        Ok((first..=peak_pos)
+            .filter(|&pos| utils::is_leaf(pos - 1))
+            .collect())
     }
 
     /// Return MMR peak hashes as a vec
     ///
     /// Peaks are listed left to right, starting with the leftmost peak. The leftmost
     /// peak is also always the 'highest' peak.
+    ///
+    /// [`Error::UnstableSize`] is returned for an unstable MMR, as are [`proof`] and
+    /// [`root`].
+    ///
+    /// [`proof`]: MerkleMountainRange::proof
+    /// [`root`]: MerkleMountainRange::root
     pub fn peaks(&self) -> Result<Vec<Hash>> {
-        let peaks = utils::peaks(self.size);
+        utils::check_size(self.size)?;
 
-        let mut hashes = Vec::new();
+        self.store.peak_hashes(self.size)
+    }
 
-        for p in peaks {
-            hashes.push(self.store.hash_at(p.saturating_sub(1))?);
-        }
+    /// Return MMR peaks as `(pos, hash)` tuples.
+    ///
+    /// Peaks are listed left to right, just like for [`MerkleMountainRange::peaks`].
+    pub fn peaks_with_positions(&self) -> Result<Vec<(u64, Hash)>> {
+        let hashes = self.peaks()?;
 
-        Ok(hashes)
+        Ok(utils::peaks(self.size).into_iter().zip(hashes).collect())
     }
 
     /// Return the root hash of the MMR.
     ///
     /// Find all the current peaks and bag them together into a single peak hash.
+    #[must_use = "calculating the root may fail"]
     pub fn root(&self) -> Result<Hash> {
         if self.size == 0 {
             return Ok(ZERO_HASH);
         }
 
+        utils::check_size(self.size)?;
+
         let mut hash = None;
         let peaks = self.peaks()?;
 
         for p in peaks.into_iter().rev() {
             hash = match hash {
                 None => Some(p),
-                Some(h) => Some(hash_with_index(self.size, &(p, h).hash())),
+                Some(h) => Some(
+                    self.index_encoding
+                        .hash_with_index(self.size, &(p, h).hash()),
+                ),
             }
         }
 
         hash.ok_or(Error::MissingRootNode)
     }
 
+    /// Return the leaf node hash `elem` is expected to have at position `pos`.
+    ///
+    /// This is the hash [`MerkleMountainRange::append`] stores for `elem`, i.e. the hash
+    /// of the SCALE encoding of `elem`, mixed with the index `pos - 1` using the MMR
+    /// index encoding. An error is returned, if `pos` is not a leaf position.
+    pub fn expected_leaf_hash(&self, pos: u64, elem: &T) -> Result<Hash> {
+        utils::check_pos(pos, self.size)?;
+
+        if !utils::is_leaf(pos - 1) {
+            return Err(Error::ExpectingLeafNode(pos));
+        }
+
+        Ok(self
+            .index_encoding
+            .hash_with_index(pos - 1, &elem.encode().hash()))
+    }
+
+    /// Return the MMR header, i.e. the MMR size together with its peak hashes.
+    pub fn header(&self) -> Result<MmrHeader> {
+        Ok(MmrHeader {
+            size: self.size,
+            peaks: self.peaks()?,
+        })
+    }
+
+    /// Return the minimal state to resume appending to the MMR, i.e. the MMR size
+    /// together with its peak hashes. See [`CompactMmr::resume`].
+    pub fn compact(&self) -> Result<CompactMmr> {
+        utils::check_size(self.size)?;
+
+        Ok(CompactMmr {
+            size: self.size,
+            peaks: self.peaks()?,
+        })
+    }
+
+    /// Return a digest over the entire store, i.e. the MMR size followed by every
+    /// node hash in index order.
+    ///
+    /// Unlike the root, the digest covers all nodes, so tampering with any stored hash
+    /// changes it. An error is returned, if any node hash is not available, e.g. because
+    /// it has been pruned.
+    pub fn integrity_digest(&self) -> Result<Hash> {
+        let mut h = Blake2b::<U32>::new();
+        h.update(self.size.to_le_bytes());
+
+        for idx in 0..self.size {
+            h.update(self.store.hash_at(idx)?);
+        }
+
+        Ok(Hash::from_vec(&h.finalize()))
+    }
+
+    /// Return the number of Blake2b hash invocations the next
+    /// [`MerkleMountainRange::append`] will perform, i.e. two for every new node.
+    pub fn append_hash_ops(&self) -> u64 {
+        utils::append_hash_ops(self.size)
+    }
+
+    /// Return the position of the most recently appended leaf or `None`, if the MMR
+    /// is empty.
+    pub fn last_leaf_pos(&self) -> Option<u64> {
+        if self.size == 0 {
+            return None;
+        }
+
+        // the last node is a peak, its rightmost leaf is `height` positions before it
+        Some(self.size - utils::height_at_pos(self.size))
+    }
+
     /// Return MMR size, i.e. total number of nodes.
     pub fn size(&self) -> u64 {
         self.size
     }
 
+    /// Return the number of MMR peaks, without fetching them.
+    ///
+    /// `0` is returned for an empty or unstable MMR.
+    pub fn peak_count(&self) -> usize {
+        utils::expected_peak_count(self.size)
+    }
+
+    /// Return a reference to the MMR store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
     /// Calculate a single MMR root by 'bagging the peaks'.
     ///
     /// Return the number of new nodes added as well as a merkle path to the MMR root.
@@ -263,7 +1186,7 @@ where
             idx += 1; // idx for new peak
 
             peak_hash = (left_hash, peak_hash).hash();
-            peak_hash = hash_with_index(idx, &peak_hash);
+            peak_hash = self.index_encoding.hash_with_index(idx, &peak_hash);
             merkle_path.push(peak_hash);
 
             height *= 2; // next power of 2
@@ -277,11 +1200,11 @@ where
     ///
     /// The returned path vector will contain the peak hashes from rigth to left,
     /// i.e. from the lowest to the highest peak.
-    fn peak_path(&self, pos: u64) -> Vec<Hash> {
-        let lower = self.bag_lower_peaks(pos);
+    fn peak_path(&self, pos: u64, size: u64) -> Vec<Hash> {
+        let lower = self.bag_lower_peaks(pos, size);
 
         // path with higher peaks, if there are any
-        let mut path = utils::peaks(self.size)
+        let mut path = utils::peaks(size)
             .into_iter()
             .filter(|&n| n < pos)
             .filter_map(|n| self.hash(n).ok())
@@ -300,8 +1223,8 @@ where
     ///
     /// Peaks are ordered left to right. The leftmost peak is always the 'highest' peak.
     /// Due to this oredering, a 'lower' peak will always have a **higher** index.
-    fn bag_lower_peaks(&self, pos: u64) -> Option<Hash> {
-        let peaks = utils::peaks(self.size)
+    fn bag_lower_peaks(&self, pos: u64, size: u64) -> Option<Hash> {
+        let peaks = utils::peaks(size)
             .into_iter()
             .filter(|&x| x > pos)
             .filter_map(|x| self.hash(x).ok());
@@ -313,7 +1236,7 @@ where
                 None => Some(peak),
                 Some(hash) => {
                     let h = (peak, hash).hash();
-                    Some(hash_with_index(self.size, &h))
+                    Some(self.index_encoding.hash_with_index(size, &h))
                 }
             }
         });
@@ -321,3 +1244,36 @@ where
         hash
     }
 }
+
+impl<S> MerkleMountainRange<Hash, S>
+where
+    S: Store<Hash>,
+{
+    /// Append the hash `leaf` to the MMR without SCALE encoding and re-hashing it.
+    /// Return new MMR size.
+    ///
+    /// The leaf node hash is `hash_with_index(idx, leaf)`, i.e. [`Hashable::hash`] of
+    /// `leaf`, which is `leaf` itself. Whereas [`MerkleMountainRange::append`] stores
+    /// `hash_with_index(idx, H(leaf.encode()))`. Hence, proofs for leaves appended this
+    /// way have to be verified using [`MerkleProof::verify_typed`].
+    #[must_use = "appending may fail and leave the MMR unchanged"]
+    pub fn append_hash(&mut self, leaf: &Hash) -> Result<u64> {
+        self.append_hashed(leaf, leaf.hash())
+    }
+
+    /// Append the root of `sub` as a leaf to the MMR. Return new MMR size.
+    ///
+    /// The MMR root then commits to the roots of all appended sub-MMRs. Inclusion of
+    /// a sub-MMR root is verified like for any other leaf, i.e. using
+    /// [`MerkleProof::verify`] with the sub-MMR root as element.
+    #[must_use = "appending may fail and leave the MMR unchanged"]
+    pub fn append_root_of<T2, S2>(&mut self, sub: &MerkleMountainRange<T2, S2>) -> Result<u64>
+    where
+        T2: Clone + Decode + Encode,
+        S2: Store<T2>,
+    {
+        let root = sub.root()?;
+
+        self.append(&root)
+    }
+}
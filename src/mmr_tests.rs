@@ -16,14 +16,105 @@
 
 //! Merkle-Mountain-Range implementation unit tests
 
-use codec::Encode;
+use core::cell::Cell;
+
+use codec::{DecodeAll, Encode};
 
 use crate::{
-    hash::ZERO_HASH, hash_with_index, Error, Hash, Hashable, MerkleMountainRange, VecStore,
+    hash::ZERO_HASH, hash_with_index, leaf_hash, utils, AppendInfo, AppendResult, CompactMmr,
+    CountingStore, Error, Hash, Hashable, IndexEncoding, IndexedVecStore, MerkleMountainRange,
+    Store, ValidationReport, VecStore, VerificationDiagnosis,
 };
 
 type E = Vec<u8>;
 
+/// Store forwarding to a counting `VecStore`, with hooks for injecting faults
+struct TestStore {
+    inner: CountingStore<VecStore<E>>,
+    // fail the append with this number once, without changing the store
+    fail_at: Option<u64>,
+    // corrupt the leaf hash of every append
+    corrupt: bool,
+    // if set, track the peaks and serve `peak_hashes` from them
+    peaks: Option<Vec<Hash>>,
+    // fail every `peak_hashes` call
+    fail_peaks: bool,
+    // number of `hash_at` and `get_many` calls, as opposed to node reads
+    calls: Cell<(u64, u64)>,
+}
+
+impl TestStore {
+    fn new() -> Self {
+        TestStore::with_store(VecStore::new())
+    }
+
+    fn with_store(store: VecStore<E>) -> Self {
+        TestStore {
+            inner: CountingStore::new(store),
+            fail_at: None,
+            corrupt: false,
+            peaks: None,
+            fail_peaks: false,
+            calls: Cell::new((0, 0)),
+        }
+    }
+}
+
+impl Store<E> for TestStore {
+    fn hash_at(&self, index: u64) -> Result<Hash, Error> {
+        let (hash_at, get_many) = self.calls.get();
+        self.calls.set((hash_at + 1, get_many));
+        self.inner.hash_at(index)
+    }
+
+    fn get_many(&self, indices: &[u64]) -> Result<Vec<Hash>, Error> {
+        let (hash_at, get_many) = self.calls.get();
+        self.calls.set((hash_at, get_many + 1));
+        self.inner.get_many(indices)
+    }
+
+    fn peak_hashes(&self, size: u64) -> Result<Vec<Hash>, Error> {
+        if self.fail_peaks {
+            return Err(Error::StoreIo("peaks".to_string()));
+        }
+
+        match &self.peaks {
+            Some(peaks) => Ok(peaks.clone()),
+            None => self.inner.peak_hashes(size),
+        }
+    }
+
+    fn append(&mut self, elem: &E, hashes: &[Hash]) -> Result<(), Error> {
+        if self.fail_at == Some(self.inner.writes() + 1) {
+            self.fail_at = None;
+            return Err(Error::StoreIo("transient".to_string()));
+        }
+
+        let mut hashes = hashes.to_vec();
+
+        if self.corrupt {
+            hashes[0].0[0] ^= 1;
+        }
+
+        self.inner.append(elem, &hashes)?;
+
+        if self.peaks.is_some() {
+            let size = Store::<E>::len(&self.inner);
+            self.peaks = Some(self.inner.peak_hashes(size)?);
+        }
+
+        Ok(())
+    }
+
+    fn undo_append(&mut self, nodes: u64) -> Result<(), Error> {
+        self.inner.undo_append(nodes)
+    }
+
+    fn len(&self) -> u64 {
+        Store::<E>::len(&self.inner)
+    }
+}
+
 fn make_mmr(num_leafs: u8) -> MerkleMountainRange<E, VecStore<E>> {
     let s = VecStore::<E>::new();
     let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
@@ -50,6 +141,350 @@ fn new_works() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn rebuild_from_leaves_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    mmr.store.hashes[4] = Hash::from_hex("0xcafe")?;
+    mmr.store.hashes[17] = Hash::from_hex("0xbabe")?;
+
+    assert!(mmr.validate().is_err());
+    assert_ne!(root, mmr.root()?);
+
+    let leaves = mmr.store.data.take().unwrap();
+    let mmr = MerkleMountainRange::rebuild_from_leaves(&leaves, VecStore::<E>::new())?;
+
+    assert_eq!(19, mmr.size());
+    assert!(mmr.validate()?);
+    assert_eq!(root, mmr.root()?);
+
+    Ok(())
+}
+
+#[test]
+fn rebuild_from_leaves_fails() {
+    let mmr = make_mmr(2);
+
+    let want = Error::StoreDesync(0, 3);
+    let got = MerkleMountainRange::rebuild_from_leaves(&[vec![0u8]], mmr.store)
+        .err()
+        .unwrap();
+
+    assert_eq!(want, got);
+}
+
+#[test]
+fn checkpoint_root_works() -> Result<(), Error> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s).with_checkpoint_interval(4);
+
+    for i in 0..12u8 {
+        mmr.append(&vec![i, 10])?;
+    }
+
+    assert_eq!(3, mmr.checkpoints.len());
+
+    for leaves in [4, 8, 12] {
+        assert_eq!(
+            Some(make_mmr(leaves).root()?),
+            mmr.checkpoint_root(leaves as u64)
+        );
+    }
+
+    assert_eq!(None, mmr.checkpoint_root(0));
+    assert_eq!(None, mmr.checkpoint_root(5));
+    assert_eq!(None, mmr.checkpoint_root(16));
+
+    // no checkpoints without an interval
+    assert!(make_mmr(12).checkpoints.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn checkpoint_failure_is_atomic() -> Result<(), Error> {
+    let mut mmr =
+        MerkleMountainRange::<E, TestStore>::new(0, TestStore::new()).with_checkpoint_interval(2);
+
+    mmr.append(&vec![0u8, 10])?;
+    mmr.store.fail_peaks = true;
+
+    assert_eq!(
+        Err(Error::StoreIo("peaks".to_string())),
+        mmr.append(&vec![1u8, 10])
+    );
+    assert_eq!(1, mmr.size);
+    assert_eq!(1, mmr.store.len());
+    assert!(mmr.checkpoints.is_empty());
+
+    mmr.store.fail_peaks = false;
+    mmr.append(&vec![1u8, 10])?;
+
+    assert_eq!(Some(mmr.root()?), mmr.checkpoint_root(2));
+
+    for i in 2..12u8 {
+        mmr.append(&vec![i, 10])?;
+    }
+
+    assert_eq!(6, mmr.checkpoints.len());
+    assert_eq!(Some(mmr.root()?), mmr.checkpoint_root(12));
+    assert_eq!(Some(make_mmr(6).root()?), mmr.checkpoint_root(6));
+
+    Ok(())
+}
+
+#[test]
+fn encode_decode_works() -> Result<(), Error> {
+    let mmr = make_mmr(11);
+    let bytes = mmr.encode();
+
+    let got = MerkleMountainRange::<E, VecStore<E>>::decode_all(&mut bytes.as_slice()).unwrap();
+
+    assert_eq!(19, got.size());
+    assert_eq!(mmr.root()?, got.root()?);
+    assert_eq!(mmr.store.data, got.store.data);
+    assert_eq!(mmr.store.hashes, got.store.hashes);
+
+    // MMR size not matching the store length
+    let mut bytes = bytes;
+    bytes[..8].copy_from_slice(&18u64.to_le_bytes());
+
+    assert!(MerkleMountainRange::<E, VecStore<E>>::decode_all(&mut bytes.as_slice()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn append_delta_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(3);
+    let mut replica = make_mmr(3);
+
+    for i in 3..11u8 {
+        let (size, delta) = mmr.append_delta(&vec![i, 10])?;
+
+        assert_eq!(size, delta.pos + delta.hashes.len() as u64 - 1);
+        assert_eq!(size, replica.apply_delta(&delta)?);
+        assert_eq!(mmr.root()?, replica.root()?);
+    }
+
+    assert_eq!(mmr.store.data, replica.store.data);
+    assert_eq!(mmr.store.hashes, replica.store.hashes);
+
+    Ok(())
+}
+
+#[test]
+fn apply_delta_checkpoints_work() -> Result<(), Error> {
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, VecStore::new());
+    let s = VecStore::<E>::new();
+    let mut replica = MerkleMountainRange::<E, VecStore<E>>::new(0, s).with_checkpoint_interval(4);
+
+    for i in 0..12u8 {
+        let (_, delta) = mmr.append_delta(&vec![i, 10])?;
+        replica.apply_delta(&delta)?;
+    }
+
+    assert_eq!(3, replica.checkpoints.len());
+    assert_eq!(Some(make_mmr(8).root()?), replica.checkpoint_root(8));
+    assert_eq!(Some(mmr.root()?), replica.checkpoint_root(12));
+
+    Ok(())
+}
+
+#[test]
+fn apply_delta_fails() -> Result<(), Error> {
+    let mut mmr = make_mmr(3);
+    let mut replica = make_mmr(2);
+
+    let (_, delta) = mmr.append_delta(&vec![3u8, 10])?;
+
+    assert_eq!(
+        Error::InvalidDelta(5, 4),
+        replica.apply_delta(&delta).err().unwrap()
+    );
+
+    let mut replica = make_mmr(3);
+    let mut delta = delta;
+    delta.hashes.pop();
+
+    assert_eq!(
+        Error::InvalidDeltaLength(2, 3),
+        replica.apply_delta(&delta).err().unwrap()
+    );
+
+    let (_, delta) = make_mmr(3).append_delta(&vec![3u8, 10])?;
+    let want = delta.hashes.clone();
+
+    // leaf hash not matching the element
+    let mut bad = delta.clone();
+    bad.elem = vec![4u8, 10];
+
+    assert!(matches!(
+        replica.apply_delta(&bad),
+        Err(Error::InvalidNodeHash(4, got, _)) if got == want[0]
+    ));
+
+    // tampered leaf hash
+    let mut bad = delta.clone();
+    bad.hashes[0] = ZERO_HASH;
+
+    assert_eq!(
+        Error::InvalidNodeHash(4, ZERO_HASH, want[0]),
+        replica.apply_delta(&bad).err().unwrap()
+    );
+
+    // tampered peak hash, i.e. a wrong root
+    let mut bad = delta.clone();
+    bad.hashes[2] = ZERO_HASH;
+
+    assert_eq!(
+        Error::InvalidNodeHash(6, ZERO_HASH, want[2]),
+        replica.apply_delta(&bad).err().unwrap()
+    );
+
+    // nothing has been appended
+    assert_eq!(4, replica.size);
+    assert_eq!(4, replica.store.len());
+
+    replica.apply_delta(&delta)?;
+
+    assert_eq!(mmr.root()?, replica.root()?);
+
+    Ok(())
+}
+
+#[test]
+fn append_encoded_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(0);
+    let mut encoded = make_mmr(0);
+
+    for i in 1..11u8 {
+        let elem = vec![i, 10];
+        assert_eq!(mmr.append(&elem)?, encoded.append_encoded(&elem.encode())?);
+    }
+
+    assert_eq!(mmr.store.hashes, encoded.store.hashes);
+    assert_eq!(mmr.store.data, encoded.store.data);
+
+    assert_eq!(
+        Error::InvalidLeafEncoding,
+        encoded.append_encoded(&[8u8, 1]).err().unwrap()
+    );
+    assert_eq!(mmr.size, encoded.size);
+
+    Ok(())
+}
+
+#[test]
+fn try_append_works() -> Result<(), Error> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    let want = [(1, 1, 0), (3, 2, 1), (4, 4, 2), (7, 5, 3)];
+
+    for (i, &(size, leaf_pos, leaf_index)) in want.iter().enumerate() {
+        let res = mmr.try_append(&vec![i as u8, 10])?;
+
+        assert_eq!(
+            AppendResult {
+                size,
+                leaf_pos,
+                leaf_index
+            },
+            res
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn projected_proof_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(5);
+    let root = mmr.root()?;
+
+    let future = (5..11u8).map(|i| vec![i, 10]).collect::<Vec<_>>();
+
+    let projected = mmr.projected_proof(5, &future)?;
+
+    // the MMR itself is unchanged
+    assert_eq!(8, mmr.size);
+    assert_eq!(root, mmr.root()?);
+
+    for leaf in &future {
+        mmr.append(leaf)?;
+    }
+
+    assert_eq!(mmr.proof(5)?, projected);
+    assert!(projected.verify(mmr.root()?, &vec![3u8, 10], 5)?);
+
+    // no future leaves, same as a regular proof
+    assert_eq!(mmr.proof(16)?, mmr.projected_proof(16, &[])?);
+
+    Ok(())
+}
+
+#[test]
+fn append_all_tracked_works() -> Result<(), Error> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    let got = mmr.append_all_tracked((0..5u8).map(|i| vec![i, 10]))?;
+
+    assert_eq!(vec![1, 2, 4, 5, 8], got);
+    assert_eq!(8, mmr.size);
+    assert_eq!(make_mmr(5).root()?, mmr.root()?);
+
+    let got = mmr.append_all_tracked(vec![])?;
+
+    assert!(got.is_empty());
+    assert_eq!(8, mmr.size);
+
+    Ok(())
+}
+
+#[test]
+fn append_info_works() -> Result<(), Error> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    let merges = (1..=8u8)
+        .map(|i| mmr.append_info(&vec![i, 10]).map(|info| info.merges))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(vec![0, 1, 0, 2, 0, 1, 0, 3], merges);
+
+    let info = mmr.append_info(&vec![9u8, 10])?;
+
+    assert_eq!(
+        AppendInfo {
+            size: 16,
+            new_nodes: 1,
+            merges: 0
+        },
+        info
+    );
+
+    Ok(())
+}
+
+#[test]
+fn leaf_data_all_works() -> Result<(), Error> {
+    let mmr = make_mmr(6);
+
+    let want = (0..6u8).map(|i| vec![i, 10]).collect::<Vec<_>>();
+
+    assert_eq!(want, mmr.leaf_data_all()?);
+
+    let mut mmr = make_mmr(6);
+    mmr.store.data = None;
+
+    assert_eq!(Error::DataNotRetained, mmr.leaf_data_all().err().unwrap());
+
+    Ok(())
+}
+
 #[test]
 fn append_two_nodes() -> Result<(), Error> {
     let s = VecStore::<E>::new();
@@ -91,6 +526,168 @@ fn append_tree_nodes() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn append_desync_fails() -> Result<(), Error> {
+    let mut mmr = make_mmr(2);
+
+    // store is ahead of the MMR
+    mmr.store.hashes.push(Hash::from_hex("0xcafe")?);
+
+    let want = Error::StoreDesync(3, 4);
+    let got = mmr.append(&vec![2u8, 10]).err().unwrap();
+
+    assert_eq!(want, got);
+    assert_eq!(3, mmr.size());
+
+    // store is behind the MMR
+    mmr.store.hashes.truncate(2);
+
+    let want = Error::StoreDesync(3, 2);
+    let got = mmr.append(&vec![2u8, 10]).err().unwrap();
+
+    assert_eq!(want, got);
+
+    Ok(())
+}
+
+#[test]
+fn reserve_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(11);
+    let want = make_mmr(111);
+
+    assert!(mmr.store.hashes.capacity() < 216);
+
+    // 111 leaves result in a MMR with 216 nodes
+    mmr.reserve(100);
+
+    assert!(mmr.store.hashes.capacity() >= 216);
+
+    (11..111).for_each(|i| {
+        let _ = mmr.append(&vec![i, 10]).unwrap();
+    });
+
+    assert_eq!(want.size, mmr.size);
+    assert_eq!(want.root()?, mmr.root()?);
+
+    Ok(())
+}
+
+#[test]
+fn reserve_overflow_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    mmr.reserve(u64::MAX);
+    mmr.reserve(u64::MAX / 2 + 1);
+
+    assert_eq!(19, mmr.size);
+    assert_eq!(root, mmr.root()?);
+
+    let mut mmr = MerkleMountainRange::<E, IndexedVecStore<E>>::new(0, IndexedVecStore::new());
+
+    mmr.reserve(u64::MAX);
+    mmr.append(&vec![0u8, 10])?;
+
+    assert_eq!(make_mmr(1).root()?, mmr.root()?);
+
+    Ok(())
+}
+
+#[test]
+fn reserve_avoids_reallocation() -> Result<(), Error> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    mmr.reserve(1000);
+
+    let hashes = mmr.store.hashes.capacity();
+    let data = mmr.store.data.as_ref().unwrap().capacity();
+
+    for i in 0..1000u32 {
+        mmr.append(&i.to_le_bytes().to_vec())?;
+    }
+
+    assert_eq!(hashes, mmr.store.hashes.capacity());
+    assert_eq!(data, mmr.store.data.as_ref().unwrap().capacity());
+
+    Ok(())
+}
+
+#[test]
+fn prune_to_peaks_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(11);
+    let root = mmr.root()?;
+    let peaks = mmr.peaks()?;
+
+    mmr.prune_to_peaks()?;
+
+    assert!(mmr.store.hashes.is_empty());
+    assert_eq!(19, mmr.store.pruned);
+    assert_eq!(root, mmr.root()?);
+    assert_eq!(peaks, mmr.peaks()?);
+
+    assert_eq!(Error::Pruned(0), mmr.proof(1).err().unwrap());
+    assert_eq!(Error::Pruned(16), mmr.proof(17).err().unwrap());
+
+    // a leaf being a peak itself can still be proven
+    let proof = mmr.proof(19)?;
+    assert!(proof.verify(root, &vec![10u8, 10], 19)?);
+
+    (11..15).for_each(|i| {
+        let _ = mmr.append(&vec![i, 10]).unwrap();
+    });
+
+    let want = make_mmr(15);
+
+    assert_eq!(want.size, mmr.size);
+    assert_eq!(want.root()?, mmr.root()?);
+
+    // pruning again only retains the new peaks
+    mmr.prune_to_peaks()?;
+
+    assert_eq!(want.root()?, mmr.root()?);
+    assert_eq!(
+        vec![14, 21, 24, 25],
+        mmr.store.retained.iter().map(|r| r.0).collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn shrink_to_fit_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(11);
+    let capacity = mmr.store.hashes.capacity();
+
+    mmr.prune_to_peaks()?;
+
+    assert_eq!(capacity, mmr.store.hashes.capacity());
+
+    mmr.shrink_to_fit();
+
+    assert!(mmr.store.hashes.capacity() < capacity);
+    assert_eq!(0, mmr.store.hashes.capacity());
+    assert_eq!(make_mmr(11).root()?, mmr.root()?);
+
+    Ok(())
+}
+
+#[test]
+fn last_leaf_pos_works() -> Result<(), Error> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    assert_eq!(None, mmr.last_leaf_pos());
+
+    for (i, want) in [1, 2, 4, 5, 8, 9, 11, 12, 16].iter().enumerate() {
+        mmr.append(&vec![i as u8, 10])?;
+
+        assert_eq!(Some(*want), mmr.last_leaf_pos());
+    }
+
+    Ok(())
+}
+
 #[test]
 fn validate_works() -> Result<(), Error> {
     let s = VecStore::<E>::new();
@@ -131,18 +728,331 @@ fn validate_fails() -> Result<(), Error> {
     let want = "invalid node hash at idx 2: 000000000000 != dac8901f3f3a".to_string();
     let got = format!("{}", got);
 
-    assert_eq!(want, got);
+    assert_eq!(want, got);
+
+    let mut mmr = make_mmr(7);
+
+    mmr.store.hashes[6] = Hash::from_hex("0x00")?;
+    let got = mmr.validate().err().unwrap();
+
+    // compare the actual error messages
+    let want = "invalid node hash at idx 6: 000000000000 != 58d5c10b0190".to_string();
+    let got = format!("{}", got);
+
+    assert_eq!(want, got);
+
+    Ok(())
+}
+
+#[test]
+fn proof_path_only_works() -> Result<(), Error> {
+    let mmr = make_mmr(11);
+
+    for pos in (1..=mmr.size).filter(|&p| utils::is_leaf(p - 1)) {
+        assert_eq!(mmr.proof(pos)?, mmr.proof_path_only(pos)?);
+    }
+
+    // retain the proof nodes only, but not the leaf
+    let mut mmr = make_mmr(11);
+    let want = mmr.proof(5)?;
+    let retain = utils::proof_positions(5, mmr.size)
+        .into_iter()
+        .map(|p| p - 1)
+        .collect::<Vec<_>>();
+
+    mmr.store.prune(&retain)?;
+
+    assert_eq!(Err(Error::Pruned(4)), mmr.proof(5));
+    assert_eq!(want, mmr.proof_path_only(5)?);
+
+    assert_eq!(Err(Error::ExpectingLeafNode(3)), mmr.proof_path_only(3));
+
+    Ok(())
+}
+
+#[test]
+fn explain_verification_failure_works() -> Result<(), Error> {
+    let mmr = make_mmr(11);
+    let elem = vec![2u8, 10].encode();
+    let proof = mmr.proof(4)?;
+
+    assert_eq!(
+        VerificationDiagnosis::Valid,
+        mmr.explain_verification_failure(&proof, &elem, 4)
+    );
+
+    assert_eq!(
+        VerificationDiagnosis::InvalidPosition(3),
+        mmr.explain_verification_failure(&proof, &elem, 3)
+    );
+    assert_eq!(
+        VerificationDiagnosis::InvalidPosition(20),
+        mmr.explain_verification_failure(&proof, &elem, 20)
+    );
+
+    let other = vec![3u8, 10].encode();
+    let want = hash_with_index(3, &other.hash());
+
+    assert_eq!(
+        VerificationDiagnosis::LeafHashMismatch(want, mmr.hash(4)?),
+        mmr.explain_verification_failure(&proof, &other, 4)
+    );
+
+    let old = make_mmr(7);
+    let old_proof = old.proof(4)?;
+
+    assert_eq!(
+        VerificationDiagnosis::SizeMismatch(11, 19),
+        mmr.explain_verification_failure(&old_proof, &elem, 4)
+    );
+
+    let mut short = proof.clone();
+    short.path.pop();
+
+    assert_eq!(
+        VerificationDiagnosis::PathTooShort(3, 4),
+        mmr.explain_verification_failure(&short, &elem, 4)
+    );
+
+    let mut long = proof.clone();
+    long.path.push(Hash::default());
+
+    assert_eq!(
+        VerificationDiagnosis::PathTooLong(5, 4),
+        mmr.explain_verification_failure(&long, &elem, 4)
+    );
+
+    let mut wrong = proof;
+    wrong.path[0] = Hash::default();
+
+    assert!(matches!(
+        mmr.explain_verification_failure(&wrong, &elem, 4),
+        VerificationDiagnosis::RootMismatch(_, root) if root == mmr.root()?
+    ));
+
+    let mut mmr = make_mmr(11);
+    mmr.prune_to_peaks()?;
+
+    assert_eq!(
+        VerificationDiagnosis::StoreError(Error::Pruned(3)),
+        mmr.explain_verification_failure(&wrong, &elem, 4)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn verify_self_works() -> Result<(), Error> {
+    let mmr = make_mmr(7);
+
+    assert_eq!(mmr.root()?, mmr.verify_self()?);
+
+    let mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, VecStore::new());
+
+    assert_eq!(ZERO_HASH, mmr.verify_self()?);
+
+    let mut mmr = make_mmr(7);
+    let want = mmr.store.hashes[6];
+
+    mmr.store.hashes[6] = Hash::default();
+
+    assert_eq!(
+        Err(Error::InvalidNodeHash(6, Hash::default(), want)),
+        mmr.verify_self()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_range_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(11);
+
+    assert!(mmr.validate_range(1, 19)?);
+
+    mmr.store.hashes[13] = Hash::from_hex("0x00")?;
+
+    assert!(mmr.validate_range(1, 13)?);
+    assert!(mmr.validate_range(16, 19)?);
+    assert!(matches!(
+        mmr.validate_range(14, 14).err().unwrap(),
+        Error::InvalidNodeHash(13, _, _)
+    ));
+    assert_eq!(
+        Error::PositionOutOfRange(20, 19),
+        mmr.validate_range(1, 20).err().unwrap()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn reject_empty_leaves_works() -> Result<(), Error> {
+    let mut mmr = MerkleMountainRange::<(), VecStore<()>>::new(0, VecStore::new());
+
+    assert_eq!(1, mmr.append(&())?);
+    assert_eq!(3, mmr.append(&())?);
+
+    let mut mmr = MerkleMountainRange::<(), VecStore<()>>::new(0, VecStore::new())
+        .with_reject_empty_leaves(true);
+
+    assert_eq!(Err(Error::EmptyLeaf), mmr.append(&()));
+    assert_eq!(Err(Error::EmptyLeaf), mmr.append_encoded(&[]));
+    assert_eq!(0, mmr.size);
+
+    // an empty projected or replicated leaf is rejected as well
+    assert_eq!(Err(Error::EmptyLeaf), mmr.projected_proof(1, &[()]));
+
+    let mut other = MerkleMountainRange::<(), VecStore<()>>::new(0, VecStore::new());
+    let (_, delta) = other.append_delta(&())?;
+
+    assert_eq!(Err(Error::EmptyLeaf), mmr.apply_delta(&delta));
+    assert_eq!(0, mmr.size);
+
+    // elements with a non-empty encoding are accepted, even if they encode zero
+    let mut mmr = MerkleMountainRange::<bool, VecStore<bool>>::new(0, VecStore::new())
+        .with_reject_empty_leaves(true);
+
+    assert_eq!(1, mmr.append(&false)?);
+
+    let mut mmr = MerkleMountainRange::<u8, VecStore<u8>>::new(0, VecStore::new())
+        .with_reject_empty_leaves(true);
+
+    assert_eq!(1, mmr.append(&0u8)?);
+
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, VecStore::new())
+        .with_reject_empty_leaves(true);
+
+    assert_eq!(1, mmr.append(&vec![])?);
+
+    Ok(())
+}
+
+#[test]
+fn append_transient_error_works() -> Result<(), Error> {
+    let s = TestStore {
+        fail_at: Some(4),
+        ..TestStore::new()
+    };
+    let mut mmr = MerkleMountainRange::<E, TestStore>::new(0, s);
+
+    for i in 0..3u8 {
+        mmr.append(&vec![i, 10])?;
+    }
+
+    // the 4th leaf would merge two peaks, adding 3 nodes
+    let err = mmr.append(&vec![3u8, 10]);
+
+    assert_eq!(Err(Error::StoreIo("transient".to_string())), err);
+    assert_eq!(4, mmr.size);
+    assert_eq!(4, Store::<E>::len(&mmr.store));
+    assert_eq!(make_mmr(3).root()?, mmr.root()?);
+
+    // retry
+    for i in 3..8u8 {
+        mmr.append(&vec![i, 10])?;
+    }
+
+    assert_eq!(15, Store::<E>::len(&mmr.store));
+    assert_eq!(make_mmr(8).root()?, mmr.root()?);
+    assert!(mmr.validate()?);
+
+    Ok(())
+}
+
+#[test]
+fn paranoid_append_works() -> Result<(), Error> {
+    let s = TestStore {
+        corrupt: true,
+        ..TestStore::new()
+    };
+    let mut mmr = MerkleMountainRange::<E, TestStore>::new(0, s);
+
+    // a corrupted leaf goes unnoticed
+    mmr.append(&vec![0u8, 10])?;
+    mmr.append(&vec![1u8, 10])?;
+
+    let s = TestStore {
+        corrupt: true,
+        ..TestStore::new()
+    };
+    let mut mmr = MerkleMountainRange::<E, TestStore>::new(0, s).with_paranoid(true);
+
+    // a corrupted lone leaf is rejected as well
+    let want = hash_with_index(0, &vec![0u8, 10].encode().hash());
+    let mut stored = want;
+    stored.0[0] ^= 1;
+
+    assert_eq!(
+        Err(Error::InvalidNodeHash(0, stored, want)),
+        mmr.append(&vec![0u8, 10])
+    );
+
+    // neither the MMR nor the store are extended
+    assert_eq!(0, mmr.size);
+    assert_eq!(0, Store::<E>::len(&mmr.store));
+
+    // appending succeeds once the store is sane again
+    mmr.store.corrupt = false;
+
+    for i in 0..11u8 {
+        mmr.append(&vec![i, 10])?;
+    }
+
+    assert_eq!(make_mmr(11).root()?, mmr.root()?);
+    assert_eq!(11, mmr.store.inner.inner().data.as_ref().unwrap().len());
+
+    // a corrupted append with merged peaks is rolled back as a whole
+    mmr.store.corrupt = true;
+    assert!(mmr.append(&vec![11u8, 10]).is_err());
+    assert_eq!(19, mmr.size);
+    assert_eq!(19, Store::<E>::len(&mmr.store));
+
+    mmr.store.corrupt = false;
+    mmr.append(&vec![11u8, 10])?;
+
+    assert_eq!(make_mmr(12).root()?, mmr.root()?);
+
+    // paranoid mode passes for a sane store
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s).with_paranoid(true);
+
+    for i in 0..11u8 {
+        mmr.append(&vec![i, 10])?;
+    }
+
+    assert_eq!(make_mmr(11).root()?, mmr.root()?);
+
+    Ok(())
+}
 
-    let mut mmr = make_mmr(7);
+#[test]
+fn validate_report_works() -> Result<(), Error> {
+    let mut mmr = make_mmr(11);
 
-    mmr.store.hashes[6] = Hash::from_hex("0x00")?;
-    let got = mmr.validate().err().unwrap();
+    assert_eq!(
+        ValidationReport {
+            checked_nodes: 19,
+            inner_nodes: 8,
+            first_invalid: None,
+            all_invalid: vec![],
+        },
+        mmr.validate_report()
+    );
 
-    // compare the actual error messages
-    let want = "invalid node hash at idx 6: 000000000000 != 58d5c10b0190".to_string();
-    let got = format!("{}", got);
+    // a corrupted leaf invalidates its parent, a corrupted parent itself and its parent
+    mmr.store.hashes[3] = Hash::from_hex("0x00")?;
+    mmr.store.hashes[13] = Hash::from_hex("0x00")?;
 
-    assert_eq!(want, got);
+    assert_eq!(
+        ValidationReport {
+            checked_nodes: 19,
+            inner_nodes: 8,
+            first_invalid: Some(6),
+            all_invalid: vec![6, 14, 15],
+        },
+        mmr.validate_report()
+    );
 
     Ok(())
 }
@@ -192,6 +1102,25 @@ fn proof_works() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn proof_single_fetch() -> Result<(), Error> {
+    let mmr = make_mmr(11);
+    let want = mmr.proof(5)?;
+
+    let store = TestStore::with_store(mmr.store);
+    let mmr = MerkleMountainRange::<E, TestStore>::new(mmr.size, store);
+
+    // the leaf and every proof node are fetched at once, each read exactly once
+    assert_eq!(want, mmr.proof(5)?);
+    assert_eq!((0, 1), mmr.store.calls.get());
+    assert_eq!(
+        1 + utils::proof_positions(5, mmr.size).len() as u64,
+        mmr.store.inner.reads()
+    );
+
+    Ok(())
+}
+
 #[test]
 fn partial_prove_works() -> Result<(), Error> {
     let mut mmr = make_mmr(4);
@@ -218,16 +1147,107 @@ fn partial_prove_works() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn append_hash_ops_works() -> Result<(), Error> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    for i in 0..20u8 {
+        let (peak_map, _) = utils::peak_height_map(mmr.size);
+        let (new, _) = mmr.bag_the_peaks(ZERO_HASH, peak_map)?;
+
+        assert_eq!(2 * new, mmr.append_hash_ops());
+
+        let size = mmr.size;
+        mmr.append(&vec![i, 10])?;
+
+        assert_eq!(new, mmr.size - size);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn partial_proof_leaf_positions() -> Result<(), Error> {
+    let mut mmr = make_mmr(4);
+
+    mmr.append(&vec![4u8, 10])?;
+    mmr.append(&vec![5u8, 10])?;
+
+    // leaf classification has to match `proof()`
+    for pos in 1..=4 {
+        let want = make_mmr(4).proof(pos);
+        let got = mmr.partial_proof(pos, 7);
+
+        assert_eq!(want, got);
+    }
+
+    assert_eq!(
+        Error::ExpectingLeafNode(3),
+        mmr.partial_proof(3, 7).err().unwrap()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn partial_proof_rightmost_peak() -> Result<(), Error> {
+    let mut mmr = make_mmr(7);
+    let root = mmr.root()?;
+
+    for i in 7..11u8 {
+        mmr.append(&vec![i, 10])?;
+    }
+
+    // leaf 11 is the rightmost peak at size 11, the higher peaks 7 and 10 are
+    // still required
+    let proof = mmr.partial_proof(11, 11)?;
+
+    assert_eq!(make_mmr(7).proof(11)?, proof);
+    assert!(proof.verify(root, &vec![6u8, 10], 11)?);
+
+    // peaks have to be bagged for the historical size, not the current one
+    let proof = mmr.partial_proof(8, 11)?;
+
+    assert_eq!(make_mmr(7).proof(8)?, proof);
+    assert!(proof.verify(root, &vec![4u8, 10], 8)?);
+
+    // a partial proof for the current size is a complete proof
+    for pos in [1, 2, 4, 5, 8, 9, 11, 12, 16, 17, 19].iter() {
+        assert_eq!(mmr.proof(*pos)?, mmr.partial_proof(*pos, mmr.size)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn partial_proof_fails() {
+    let mmr = make_mmr(8);
+
+    assert_eq!(
+        Error::FutureSize(19, 15),
+        mmr.partial_proof(11, 19).err().unwrap()
+    );
+    assert_eq!(
+        Error::UnstableSize(9),
+        mmr.partial_proof(4, 9).err().unwrap()
+    );
+    assert_eq!(
+        Error::UnstableSize(2),
+        mmr.partial_proof(1, 2).err().unwrap()
+    );
+}
+
 #[test]
 fn bag_lower_peaks_works() -> Result<(), Error> {
     let mmr = make_mmr(2);
-    let got = mmr.bag_lower_peaks(3);
+    let got = mmr.bag_lower_peaks(3, mmr.size);
 
     assert_eq!(None, got);
 
     let mmr = make_mmr(3);
     let want = mmr.hash(4)?;
-    let got = mmr.bag_lower_peaks(3).unwrap();
+    let got = mmr.bag_lower_peaks(3, mmr.size).unwrap();
 
     assert_eq!(want, got);
 
@@ -236,30 +1256,98 @@ fn bag_lower_peaks_works() -> Result<(), Error> {
     let h2 = mmr.hash(11)?;
     let want = (h1, h2).hash();
     let want = hash_with_index(mmr.size, &want);
-    let got = mmr.bag_lower_peaks(7).unwrap();
+    let got = mmr.bag_lower_peaks(7, mmr.size).unwrap();
 
     assert_eq!(want, got);
 
     Ok(())
 }
 
+#[test]
+fn bag_lower_peaks_many_peaks_works() -> Result<(), Error> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    for i in 0..1000u16 {
+        mmr.append(&i.encode())?;
+    }
+
+    let peaks = utils::peaks(mmr.size);
+
+    assert_eq!(6, peaks.len());
+
+    for (i, &pos) in peaks.iter().enumerate() {
+        // bag the lower peaks right to left, exactly as `root()` does
+        let want = peaks[i + 1..].iter().rev().try_fold(None, |acc, &p| {
+            let peak = mmr.hash(p)?;
+
+            Ok::<_, Error>(Some(match acc {
+                None => peak,
+                Some(h) => hash_with_index(mmr.size, &(peak, h).hash()),
+            }))
+        })?;
+
+        assert_eq!(want, mmr.bag_lower_peaks(pos, mmr.size));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn peak_path_many_peaks_works() -> Result<(), Error> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    for i in 0..1000u16 {
+        mmr.append(&i.encode())?;
+    }
+
+    let root = mmr.root()?;
+    let peaks = utils::peaks(mmr.size);
+
+    for (i, &pos) in peaks.iter().enumerate() {
+        let path = mmr.peak_path(pos, mmr.size);
+        let lower = peaks.len() - i - 1;
+
+        // at most one hash for all the lower peaks, plus one for each higher peak
+        assert_eq!(i + lower.min(1), path.len());
+
+        let mut path = path.into_iter();
+        let mut hash = mmr.hash(pos)?;
+
+        if lower > 0 {
+            let bagged = path.next().unwrap();
+            hash = hash_with_index(mmr.size, &(hash, bagged).hash());
+        }
+
+        for (higher, &p) in path.zip(peaks[..i].iter().rev()) {
+            assert_eq!(mmr.hash(p)?, higher);
+            hash = hash_with_index(mmr.size, &(higher, hash).hash());
+        }
+
+        assert_eq!(root, hash);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn peak_path_works() -> Result<(), Error> {
     let mmr = make_mmr(2);
-    let path = mmr.peak_path(3);
+    let path = mmr.peak_path(3, mmr.size);
 
     assert!(path.is_empty());
 
     let mmr = make_mmr(3);
     let want = mmr.hash(4)?;
     let want = vec![want];
-    let got = mmr.peak_path(3);
+    let got = mmr.peak_path(3, mmr.size);
 
     assert_eq!(want, got);
 
     let want = mmr.hash(3)?;
     let want = vec![want];
-    let got = mmr.peak_path(4);
+    let got = mmr.peak_path(4, mmr.size);
 
     assert_eq!(want, got);
 
@@ -267,14 +1355,14 @@ fn peak_path_works() -> Result<(), Error> {
     let h1 = mmr.hash(10)?;
     let h2 = mmr.hash(7)?;
     let want = vec![h1, h2];
-    let got = mmr.peak_path(11);
+    let got = mmr.peak_path(11, mmr.size);
 
     assert_eq!(want, got);
 
     let h1 = mmr.hash(11)?;
     let h2 = mmr.hash(7)?;
     let want = vec![h1, h2];
-    let got = mmr.peak_path(10);
+    let got = mmr.peak_path(10, mmr.size);
 
     assert_eq!(want, got);
 
@@ -283,7 +1371,7 @@ fn peak_path_works() -> Result<(), Error> {
     let want = (h2, h1).hash();
     let want = hash_with_index(mmr.size, &want);
     let want = vec![want];
-    let got = mmr.peak_path(7);
+    let got = mmr.peak_path(7, mmr.size);
 
     assert_eq!(want, got);
 
@@ -295,17 +1383,42 @@ fn hash_error_works() {
     let s = VecStore::<E>::new();
     let mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
 
-    let want = Error::MissingHashAtIndex(0);
+    let want = Error::ZeroPosition;
     let got = mmr.hash(0).err().unwrap();
 
     assert_eq!(want, got);
 
+    let want = Error::PositionOutOfRange(3, 0);
+    let got = mmr.hash(3).err().unwrap();
+
+    assert_eq!(want, got);
+
+    // MMR size and store are out of sync
+    let s = VecStore::<E>::new();
+    let mmr = MerkleMountainRange::<E, VecStore<E>>::new(3, s);
+
     let want = Error::MissingHashAtIndex(2);
     let got = mmr.hash(3).err().unwrap();
 
     assert_eq!(want, got);
 }
 
+#[test]
+fn position_error_works() {
+    let mmr = make_mmr(11);
+
+    assert_eq!(Error::ZeroPosition, mmr.proof(0).err().unwrap());
+    assert_eq!(
+        Error::PositionOutOfRange(20, 19),
+        mmr.proof(20).err().unwrap()
+    );
+    assert_eq!(Error::ZeroPosition, mmr.partial_proof(0, 11).err().unwrap());
+    assert_eq!(
+        Error::PositionOutOfRange(12, 11),
+        mmr.partial_proof(12, 11).err().unwrap()
+    );
+}
+
 #[test]
 fn hash_works() -> Result<(), Error> {
     let mmr = make_mmr(3);
@@ -343,6 +1456,50 @@ fn hash_works() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn inner_positions_works() {
+    let mmr = make_mmr(4);
+
+    assert_eq!(mmr.inner_positions().collect::<Vec<_>>(), [3, 6, 7]);
+
+    let mmr = make_mmr(3);
+
+    assert_eq!(mmr.inner_positions().collect::<Vec<_>>(), [3]);
+}
+
+#[test]
+fn leaves_under_peak_works() -> Result<(), Error> {
+    let mmr = make_mmr(4);
+
+    assert_eq!(vec![1, 2, 4, 5], mmr.leaves_under_peak(7)?);
+
+    let mmr = make_mmr(11);
+
+    assert_eq!(vec![1, 2, 4, 5, 8, 9, 11, 12], mmr.leaves_under_peak(15)?);
+    assert_eq!(vec![16, 17], mmr.leaves_under_peak(18)?);
+    assert_eq!(vec![19], mmr.leaves_under_peak(19)?);
+
+    Ok(())
+}
+
+#[test]
+fn leaves_under_peak_fails() {
+    let mmr = make_mmr(11);
+
+    assert_eq!(
+        Error::InvalidPeakPosition(14),
+        mmr.leaves_under_peak(14).err().unwrap()
+    );
+    assert_eq!(
+        Error::InvalidPeakPosition(20),
+        mmr.leaves_under_peak(20).err().unwrap()
+    );
+    assert_eq!(
+        Error::InvalidPeakPosition(0),
+        mmr.leaves_under_peak(0).err().unwrap()
+    );
+}
+
 #[test]
 fn peaks_works() -> Result<(), Error> {
     let mmr = make_mmr(1);
@@ -381,6 +1538,226 @@ fn peaks_works() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn integrity_digest_works() -> Result<(), Error> {
+    let mmr = make_mmr(7);
+    let want = mmr.integrity_digest()?;
+
+    assert_eq!(want, make_mmr(7).integrity_digest()?);
+    assert_ne!(want, make_mmr(6).integrity_digest()?);
+
+    for idx in 0..mmr.size as usize {
+        let mut mmr = make_mmr(7);
+        mmr.store.hashes[idx].0[0] ^= 1;
+
+        assert_ne!(want, mmr.integrity_digest()?);
+    }
+
+    // swapping two nodes keeps the set of hashes, but not their order
+    let mut mmr = make_mmr(7);
+    mmr.store.hashes.swap(0, 1);
+
+    assert_ne!(want, mmr.integrity_digest()?);
+
+    let mut mmr = make_mmr(7);
+    mmr.prune_to_peaks()?;
+
+    assert_eq!(Err(Error::Pruned(0)), mmr.integrity_digest());
+
+    Ok(())
+}
+
+#[test]
+fn ancestors_works() -> Result<(), Error> {
+    let mmr = make_mmr(8);
+
+    assert_eq!(vec![10, 14, 15], mmr.ancestors(8)?);
+    assert!(mmr.ancestors(15)?.is_empty());
+    assert_eq!(Err(Error::PositionOutOfRange(16, 15)), mmr.ancestors(16));
+
+    Ok(())
+}
+
+#[test]
+fn compact_resume_works() -> Result<(), Error> {
+    let mut want = make_mmr(11);
+    let compact = want.compact()?;
+
+    assert_eq!(19, compact.size);
+    assert_eq!(want.peaks()?, compact.peaks);
+
+    // round trip through the encoding, as for cold storage
+    let compact = CompactMmr::decode_all(&mut &compact.encode()[..]).unwrap();
+    let store = compact.peak_store();
+    let mut mmr = compact.resume::<E, _>(store)?;
+
+    assert_eq!(want.root()?, mmr.root()?);
+
+    for i in 11..20u8 {
+        want.append(&vec![i, 10])?;
+        mmr.append(&vec![i, 10])?;
+
+        assert_eq!(want.size, mmr.size);
+        assert_eq!(want.root()?, mmr.root()?);
+    }
+
+    assert_eq!(make_mmr(20).root()?, mmr.root()?);
+    assert_eq!(Err(Error::Pruned(0)), mmr.proof(1));
+
+    Ok(())
+}
+
+#[test]
+fn compact_resume_own_store_works() -> Result<(), Error> {
+    let full = make_mmr(11);
+    let compact = full.compact()?;
+    let mut mmr = compact.resume::<E, _>(full.store)?;
+
+    mmr.append(&vec![11u8, 10])?;
+
+    assert_eq!(make_mmr(12).root()?, mmr.root()?);
+    assert_eq!(12, mmr.leaf_data_all()?.len());
+
+    Ok(())
+}
+
+#[test]
+fn compact_resume_fails() -> Result<(), Error> {
+    let mut compact = make_mmr(11).compact()?;
+    compact.peaks.pop();
+
+    let store = make_mmr(11).store;
+
+    assert!(matches!(
+        compact.resume::<E, _>(store),
+        Err(Error::InvalidPeakCount(2, 3))
+    ));
+
+    let compact = CompactMmr {
+        size: 2,
+        peaks: vec![],
+    };
+
+    assert!(matches!(
+        compact.resume::<E, _>(VecStore::new()),
+        Err(Error::UnstableSize(2))
+    ));
+
+    // the store has to match the compacted state
+    let compact = make_mmr(11).compact()?;
+
+    assert!(matches!(
+        compact.clone().resume::<E, _>(make_mmr(4).store),
+        Err(Error::StoreDesync(19, 7))
+    ));
+
+    let mut other = make_mmr(10);
+    other.append(&vec![42u8])?;
+
+    assert!(matches!(
+        compact.resume::<E, _>(other.store),
+        Err(Error::InvalidNodeHash(18, ..))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn expected_leaf_hash_works() -> Result<(), Error> {
+    let mmr = make_mmr(11);
+
+    let leaves = (1..=mmr.size).filter(|&p| utils::is_leaf(p - 1));
+
+    for (i, pos) in leaves.enumerate() {
+        let elem = vec![i as u8, 10];
+
+        assert_eq!(mmr.hash(pos)?, mmr.expected_leaf_hash(pos, &elem)?);
+        assert_eq!(mmr.hash(pos)?, leaf_hash(pos, &elem.encode())?);
+    }
+
+    assert_eq!(
+        Err(Error::ExpectingLeafNode(3)),
+        mmr.expected_leaf_hash(3, &vec![])
+    );
+    assert_eq!(
+        Err(Error::PositionOutOfRange(20, 19)),
+        mmr.expected_leaf_hash(20, &vec![])
+    );
+
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, VecStore::new())
+        .with_index_encoding(IndexEncoding::BigEndian);
+
+    for i in 0..3u8 {
+        mmr.append(&vec![i, 10])?;
+    }
+
+    assert_eq!(mmr.hash(4)?, mmr.expected_leaf_hash(4, &vec![2u8, 10])?);
+    assert_ne!(make_mmr(3).hash(4)?, mmr.hash(4)?);
+
+    Ok(())
+}
+
+#[test]
+fn peak_count_works() -> Result<(), Error> {
+    let want = [(1, 1, 1), (6, 10, 2), (7, 11, 3), (11, 19, 3)];
+
+    for &(leaves, size, count) in want.iter() {
+        let mmr = make_mmr(leaves);
+
+        assert_eq!(size, mmr.size);
+        assert_eq!(count, mmr.peak_count());
+        assert_eq!(mmr.peaks()?.len(), mmr.peak_count());
+    }
+
+    let mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, VecStore::new());
+
+    assert_eq!(0, mmr.peak_count());
+
+    Ok(())
+}
+
+#[test]
+fn peak_hashes_override_works() -> Result<(), Error> {
+    let s = TestStore {
+        peaks: Some(vec![]),
+        ..TestStore::new()
+    };
+    let mut mmr = MerkleMountainRange::<E, TestStore>::new(0, s);
+
+    for i in 0..11u8 {
+        mmr.append(&vec![i, 10])?;
+    }
+
+    mmr.store.inner.reset();
+
+    // peaks are served by the override, without reading any node
+    assert_eq!(make_mmr(11).peaks()?, mmr.peaks()?);
+    assert_eq!(make_mmr(11).root()?, mmr.root()?);
+    assert_eq!(0, mmr.store.inner.reads());
+
+    Ok(())
+}
+
+#[test]
+fn peaks_with_positions_works() -> Result<(), Error> {
+    let mmr = make_mmr(11);
+
+    assert_eq!(
+        vec![
+            (15, mmr.hash(15)?),
+            (18, mmr.hash(18)?),
+            (19, mmr.hash(19)?)
+        ],
+        mmr.peaks_with_positions()?
+    );
+
+    let mmr = make_mmr(0);
+
+    assert_eq!(vec![(1, mmr.hash(1)?)], mmr.peaks_with_positions()?);
+
+    Ok(())
+}
+
 #[test]
 fn root_works() -> Result<(), Error> {
     let mmr = make_mmr(1);
@@ -422,6 +1799,45 @@ fn root_works() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn combine_works() -> Result<(), Error> {
+    let mmr = make_mmr(11);
+
+    // parent node hash as calculated by `validate()`
+    let (h1, h2) = (mmr.hash(1)?, mmr.hash(2)?);
+    let want = hash_with_index(2, &(h1, h2).hash());
+
+    assert_eq!((h1, h2).hash(), Hash::combine(&h1, &h2));
+    assert_eq!(want, Hash::combine_indexed(2, &h1, &h2));
+    assert_eq!(mmr.hash(3)?, Hash::combine_indexed(2, &h1, &h2));
+
+    // peak bagging as calculated by `root()`
+    let (p1, p2, p3) = (mmr.hash(15)?, mmr.hash(18)?, mmr.hash(19)?);
+    let h = hash_with_index(mmr.size, &(p2, p3).hash());
+    let want = hash_with_index(mmr.size, &(p1, h).hash());
+
+    let h = Hash::combine_indexed(mmr.size, &p2, &p3);
+    let got = Hash::combine_indexed(mmr.size, &p1, &h);
+
+    assert_eq!(want, got);
+    assert_eq!(mmr.root()?, got);
+
+    Ok(())
+}
+
+#[test]
+fn unstable_size_fails() {
+    for (leafs, size) in [(2, 2), (4, 5)] {
+        let mmr = make_mmr(leafs);
+        let mmr = MerkleMountainRange::<E, VecStore<E>>::new(size, mmr.store);
+
+        assert_eq!(Error::UnstableSize(size), mmr.peaks().err().unwrap());
+        assert_eq!(Error::UnstableSize(size), mmr.root().err().unwrap());
+        assert_eq!(Error::UnstableSize(size), mmr.proof(1).err().unwrap());
+        assert!(mmr.header().is_err());
+    }
+}
+
 #[test]
 fn root_fails() -> Result<(), Error> {
     let s = VecStore::<E>::new();
@@ -16,6 +16,7 @@
 
 pub extern crate alloc;
 
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
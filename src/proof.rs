@@ -16,13 +16,33 @@
 
 //! Merkle Proof for a MMR path
 
-use codec::{Decode, Encode};
+use codec::{Decode, DecodeAll, Encode};
+use scale_info::TypeInfo;
 
-use crate::{error::Error, hash_with_index, utils, Hash, Hashable, Vec};
+use crate::{
+    batch, error::Error, utils, vec, BatchProof, Hash, Hashable, IndexEncoding, Vec,
+    VerifiedProofCache,
+};
 
-#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+#[cfg(test)]
+#[path = "proof_tests.rs"]
+mod tests;
+
+/// A MMR membership proof.
+///
+/// All proof path elements are fixed-width [`struct@Hash`]es of [`Hash::LEN`] bytes, i.e.
+/// a proof is always homogeneous with respect to the hash width. Mixing hashes of
+/// different widths within a proof is not supported.
+#[derive(Clone, Debug, PartialEq, Encode, Decode, TypeInfo)]
 pub struct MerkleProof {
+    /// Size of the MMR the proof has been generated for
     pub mmr_size: u64,
+    /// Proof path hashes, ordered from the leaf towards the root.
+    ///
+    /// `path[0]` is the immediate sibling of the proven leaf, followed by the remaining
+    /// siblings up to the peak the leaf belongs to. Those are followed by a single hash
+    /// of all lower peaks bagged together, if any, and finally the higher peaks from
+    /// right to left. See [`MerkleProof::positions`] for the corresponding positions.
     pub path: Vec<Hash>,
 }
 
@@ -40,26 +60,626 @@ impl MerkleProof {
         }
     }
 
+    /// Return an iterator over the proof path hashes, starting with the sibling of
+    /// the proven leaf.
+    ///
+    /// This is the order of [`MerkleProof::path`].
+    pub fn from_leaf_to_root(&self) -> impl Iterator<Item = &Hash> {
+        self.path.iter()
+    }
+
+    /// Return an iterator over the proof path hashes in reverse order, i.e. ending
+    /// with the sibling of the proven leaf.
+    pub fn from_root_to_leaf(&self) -> impl Iterator<Item = &Hash> {
+        self.path.iter().rev()
+    }
+
+    /// Return a summary of the proof, without any of the path hashes.
+    pub fn summary(&self) -> ProofSummary {
+        ProofSummary {
+            mmr_size: self.mmr_size,
+            num_siblings: self.path.len(),
+            total_bytes: self.encoded_size(),
+        }
+    }
+
+    /// Return the maximum possible proof path length for any leaf in a MMR of
+    /// `mmr_size` nodes, i.e. the height of the tree plus its peak count.
+    ///
+    /// This allows bounded-memory verifiers to reject oversized proofs upfront.
+    pub fn max_expected_len(mmr_size: u64) -> usize {
+        let peaks = utils::peaks(mmr_size);
+        let height = peaks.first().map_or(0, |&p| utils::height_at_pos(p));
+
+        height as usize + peaks.len()
+    }
+
+    /// Return `true`, if the proof path length matches the length of a proof for a leaf
+    /// at position `pos` within a MMR of `mmr_size` nodes.
+    ///
+    /// This is a cheap structural check only, no hashing is done. `false` is returned
+    /// as well, if `pos` is not a leaf position within the MMR.
+    pub fn is_valid_shape(&self, pos: u64) -> bool {
+        utils::check_size(self.mmr_size).is_ok()
+            && utils::check_pos(pos, self.mmr_size).is_ok()
+            && utils::is_leaf(pos - 1)
+            && self.path.len() == utils::proof_len(pos, self.mmr_size)
+    }
+
+    /// Return the SCALE encoded proof path without the MMR size.
+    ///
+    /// This is useful for a batch of proofs sharing the same MMR size, which then
+    /// needs to be encoded only once. Use [`MerkleProof::decode_with_size`] to decode.
+    pub fn encode_without_size(&self) -> Vec<u8> {
+        self.path.encode()
+    }
+
+    /// Decode a proof path encoded by [`MerkleProof::encode_without_size`] and
+    /// return a proof for a MMR with `mmr_size` nodes.
+    pub fn decode_with_size(bytes: &[u8], mmr_size: u64) -> Result<MerkleProof, Error> {
+        let mut bytes = bytes;
+        let path = Vec::<Hash>::decode_all(&mut bytes).map_err(|_| Error::InvalidProofEncoding)?;
+
+        Ok(MerkleProof { mmr_size, path })
+    }
+
+    /// Return the proof as a flat byte array.
+    ///
+    /// The MMR size is encoded as 8 bytes big-endian, followed by the concatenated
+    /// path hashes. This is the layout of an `abi.encodePacked` of the proof.
+    pub fn to_flat_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + Hash::LEN * self.path.len());
+
+        bytes.extend_from_slice(&self.mmr_size.to_be_bytes());
+        self.path.iter().for_each(|h| bytes.extend_from_slice(&h.0));
+
+        bytes
+    }
+
+    /// Return a proof from a flat byte array created by [`MerkleProof::to_flat_bytes`].
+    pub fn from_flat_bytes(bytes: &[u8]) -> Result<MerkleProof, Error> {
+        if bytes.len() < 8 || !(bytes.len() - 8).is_multiple_of(Hash::LEN) {
+            return Err(Error::InvalidProofEncoding);
+        }
+
+        let (size, path) = bytes.split_at(8);
+
+        let mut mmr_size = [0u8; 8];
+        mmr_size.copy_from_slice(size);
+
+        Ok(MerkleProof {
+            mmr_size: u64::from_be_bytes(mmr_size),
+            path: path.chunks(Hash::LEN).map(Hash::from_vec).collect(),
+        })
+    }
+
     /// Verfiy that `elem` is a MMR node at positon `pos` given the root hash `root`.
+    ///
+    /// The proof is bound to `pos`. The leaf hash is salted with the caller supplied
+    /// position and the path is folded along the family path of that position. Hence,
+    /// a proof for one position fails to verify for any other position, even for the
+    /// very same element.
+    #[must_use = "a failed verification is reported as an error"]
     pub fn verify<T>(&self, root: Hash, elem: &T, pos: u64) -> Result<bool, Error>
     where
         T: Clone + Encode,
     {
+        self.verify_with_index_encoding(root, elem, pos, IndexEncoding::default())
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given the root hash `root`.
+    ///
+    /// Unlike [`MerkleProof::verify`], a proof path longer than
+    /// [`MerkleProof::max_expected_len`] is rejected with [`Error::ProofTooLong`]
+    /// before doing any hashing.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_bounded<T>(&self, root: Hash, elem: &T, pos: u64) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        let max = MerkleProof::max_expected_len(self.mmr_size);
+
+        if self.path.len() > max {
+            return Err(Error::ProofTooLong(self.path.len() as u64, max as u64));
+        }
+
+        self.verify(root, elem, pos)
+    }
+
+    /// Verify that `elem` is a MMR leaf at position `pos` below the peak with hash
+    /// `peak_hash`.
+    ///
+    /// The proof is expected to be a proof up to the peak only, as returned by
+    /// [`MerkleMountainRange::proof_within_peak`](crate::MerkleMountainRange::proof_within_peak).
+    /// [`Error::InvalidProofLength`] is returned for any other proof.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_within_peak<T>(&self, peak_hash: Hash, elem: &T, pos: u64) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        self.verify_within_peak_with_index_encoding(peak_hash, elem, pos, IndexEncoding::default())
+    }
+
+    /// Verify that `elem` is a MMR leaf at position `pos` below the peak with hash
+    /// `peak_hash`, for a MMR mixing node indices into node hashes using `encoding`.
+    ///
+    /// See [`MerkleProof::verify_within_peak`].
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_within_peak_with_index_encoding<T>(
+        &self,
+        peak_hash: Hash,
+        elem: &T,
+        pos: u64,
+        encoding: IndexEncoding,
+    ) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        utils::check_pos(pos, self.mmr_size)?;
+
+        if self.path.len() != utils::family_path(pos, self.mmr_size).len() {
+            return Err(Error::InvalidProofLength);
+        }
+
         let peaks = utils::peaks(self.mmr_size);
-        self.clone().do_verify(root, elem.encode(), pos, &peaks)
+        self.do_verify(peak_hash, elem.encode().hash(), pos, &peaks, encoding)
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given the root hash `root`.
+    ///
+    /// Along with the verification result, return the index of the peak `pos` belongs
+    /// to. Peaks are indexed left to right, starting at `0`.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_with_peak_index<T>(
+        &self,
+        root: Hash,
+        elem: &T,
+        pos: u64,
+    ) -> Result<(bool, usize), Error>
+    where
+        T: Clone + Encode,
+    {
+        utils::check_pos(pos, self.mmr_size)?;
+
+        let peak = utils::family_path(pos, self.mmr_size)
+            .last()
+            .map_or(pos, |n| n.0);
+
+        let idx = utils::peaks(self.mmr_size)
+            .iter()
+            .position(|&p| p == peak)
+            .ok_or(Error::InvalidPeakPosition(peak))?;
+
+        Ok((self.verify(root, elem, pos)?, idx))
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given the root hash `root`
+    /// of a MMR mixing node indices into node hashes using `encoding`.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_with_index_encoding<T>(
+        &self,
+        root: Hash,
+        elem: &T,
+        pos: u64,
+        encoding: IndexEncoding,
+    ) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        let peaks = utils::peaks(self.mmr_size);
+        self.do_verify(root, elem.encode().hash(), pos, &peaks, encoding)
+    }
+
+    /// Verify that the leaf node hash `leaf_node_hash` is the MMR node at position `pos`
+    /// given the root hash `root`.
+    ///
+    /// The leaf node hash is expected to be already hashed with its index, i.e. to be
+    /// `hash_with_index(pos - 1, &elem.encode().hash())` and is folded as is.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_prehashed_leaf(
+        &self,
+        root: Hash,
+        leaf_node_hash: Hash,
+        pos: u64,
+    ) -> Result<bool, Error> {
+        let peaks = utils::peaks(self.mmr_size);
+        self.fold_node(root, leaf_node_hash, pos, &peaks, IndexEncoding::default())
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given the root hash `root`,
+    /// using the caller provided peak positions `peaks`.
+    ///
+    /// Peak positions are required in strictly ascending order, i.e. left to right,
+    /// otherwise [`Error::UnsortedPeaks`] is returned.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_with_peaks<T>(
+        &self,
+        root: Hash,
+        elem: &T,
+        pos: u64,
+        peaks: &[u64],
+    ) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        if peaks.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(Error::UnsortedPeaks);
+        }
+
+        self.do_verify(
+            root,
+            elem.encode().hash(),
+            pos,
+            peaks,
+            IndexEncoding::default(),
+        )
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given the root hash `root`.
+    ///
+    /// Unlike [`MerkleProof::verify`], the leaf data hash is [`Hashable::hash`] of `elem`,
+    /// not the hash of its SCALE encoding. Both are the same for `u32` and `u64` leaves.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_typed<E>(&self, root: Hash, elem: &E, pos: u64) -> Result<bool, Error>
+    where
+        E: Hashable,
+    {
+        self.verify_typed_with_index_encoding(root, elem, pos, IndexEncoding::default())
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given the root hash `root`
+    /// of a MMR mixing node indices into node hashes using `encoding`.
+    ///
+    /// See [`MerkleProof::verify_typed`].
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_typed_with_index_encoding<E>(
+        &self,
+        root: Hash,
+        elem: &E,
+        pos: u64,
+        encoding: IndexEncoding,
+    ) -> Result<bool, Error>
+    where
+        E: Hashable,
+    {
+        let peaks = utils::peaks(self.mmr_size);
+        self.do_verify(root, elem.hash(), pos, &peaks, encoding)
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given any of the candidate
+    /// root hashes `roots`.
+    ///
+    /// Return the index of the first matching root, or `None` if no root matches.
+    #[must_use = "a failed verification is reported as `None`"]
+    pub fn verify_any<T>(&self, roots: &[Hash], elem: &T, pos: u64) -> Result<Option<usize>, Error>
+    where
+        T: Clone + Encode,
+    {
+        self.verify_any_with_index_encoding(roots, elem, pos, IndexEncoding::default())
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given any of the candidate
+    /// root hashes `roots` of a MMR mixing node indices into node hashes using `encoding`.
+    ///
+    /// See [`MerkleProof::verify_any`].
+    #[must_use = "a failed verification is reported as `None`"]
+    pub fn verify_any_with_index_encoding<T>(
+        &self,
+        roots: &[Hash],
+        elem: &T,
+        pos: u64,
+        encoding: IndexEncoding,
+    ) -> Result<Option<usize>, Error>
+    where
+        T: Clone + Encode,
+    {
+        let peaks = utils::peaks(self.mmr_size);
+        let hash = encoding.hash_with_index(node_index(pos, self.mmr_size), &elem.encode().hash());
+        let root = self.fold_root(hash, pos, &peaks, encoding);
+
+        Ok(roots.iter().position(|r| r.ct_eq(&root)))
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given the root hash `root`,
+    /// using `cache` to short-circuit repeated verifications.
+    ///
+    /// See [`VerifiedProofCache`] for what is being cached.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_cached<T>(
+        &self,
+        root: Hash,
+        elem: &T,
+        pos: u64,
+        cache: &mut VerifiedProofCache,
+    ) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        let key = (root, pos, elem.encode().hash());
+
+        if cache.lookup(&key) {
+            return Ok(true);
+        }
+
+        let res = self.verify(root, elem, pos)?;
+
+        if res {
+            cache.insert(key);
+        }
+
+        Ok(res)
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given the root hash `root`.
+    ///
+    /// If `expected_leaf_hash` is given, the leaf node hash of `elem` is checked against
+    /// it first. This allows to fail early with [`Error::LeafHashMismatch`] in case the
+    /// wrong element is passed in, instead of an opaque root hash mismatch.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_strict<T>(
+        &self,
+        root: Hash,
+        elem: &T,
+        pos: u64,
+        expected_leaf_hash: Option<Hash>,
+    ) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        self.verify_strict_with_index_encoding(
+            root,
+            elem,
+            pos,
+            expected_leaf_hash,
+            IndexEncoding::default(),
+        )
+    }
+
+    /// Verify that `elem` is a MMR node at position `pos` given the root hash `root`
+    /// of a MMR mixing node indices into node hashes using `encoding`.
+    ///
+    /// See [`MerkleProof::verify_strict`].
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify_strict_with_index_encoding<T>(
+        &self,
+        root: Hash,
+        elem: &T,
+        pos: u64,
+        expected_leaf_hash: Option<Hash>,
+        encoding: IndexEncoding,
+    ) -> Result<bool, Error>
+    where
+        T: Clone + Encode,
+    {
+        if let Some(expected) = expected_leaf_hash {
+            let hash = encoding.hash_with_index(pos.saturating_sub(1), &elem.encode().hash());
+
+            if hash != expected {
+                return Err(Error::LeafHashMismatch(hash, expected));
+            }
+        }
+
+        self.verify_with_index_encoding(root, elem, pos, encoding)
+    }
+
+    /// Return the MMR peak hashes as reconstructed by the verifier for `elem` being a
+    /// leaf at position `pos`.
+    ///
+    /// Peaks are returned left to right. A proof contains all peaks higher than the
+    /// one `elem` belongs to, but only a single hash for all lower peaks bagged together.
+    /// Hence, if `elem` is not below the rightmost peak, the last hash returned is the
+    /// hash of all lower peaks bagged together.
+    pub fn reconstruct_peaks<T>(&self, elem: &T, pos: u64) -> Result<Vec<Hash>, Error>
+    where
+        T: Clone + Encode,
+    {
+        utils::check_pos(pos, self.mmr_size)?;
+
+        let peaks = utils::peaks(self.mmr_size);
+        let mut path = self.path.iter();
+        let mut verifier = ProofVerifier::new(Hash::default(), elem, pos, self.mmr_size);
+
+        // fold up to the peak `elem` belongs to
+        let x = loop {
+            if let Ok(x) = peaks.binary_search(&verifier.pos) {
+                break x;
+            }
+
+            let sibling = path.next().ok_or(Error::InvalidProofLength)?;
+            verifier.feed(*sibling)?;
+        };
+
+        // the remaining path are the bagged lower peaks, if any, followed by the
+        // higher peaks from right to left
+        let rest = path.cloned().collect::<Vec<_>>();
+        let lower = usize::from(x != peaks.len() - 1);
+
+        if rest.len() != x + lower {
+            return Err(Error::InvalidProofLength);
+        }
+
+        let mut hashes = rest[lower..].iter().rev().cloned().collect::<Vec<_>>();
+        hashes.push(verifier.hash);
+        hashes.extend_from_slice(&rest[..lower]);
+
+        Ok(hashes)
+    }
+
+    /// Return the node positions the proof path hashes represent, for a proof of the
+    /// leaf at position `pos`.
+    ///
+    /// Positions are in proof path order, i.e. the family siblings followed by the peaks.
+    /// All peaks lower than the one `pos` belongs to are bagged into a single path hash,
+    /// which is represented by the position of the leftmost lower peak.
+    pub fn positions(&self, pos: u64) -> Vec<u64> {
+        let family = utils::family_path(pos, self.mmr_size);
+        let peak = family.last().map_or(pos, |n| n.0);
+        let peaks = utils::peaks(self.mmr_size);
+
+        let mut positions = family.iter().map(|n| n.1).collect::<Vec<_>>();
+
+        positions.extend(peaks.iter().find(|&&p| p > peak));
+        positions.extend(peaks.iter().rev().filter(|&&p| p < peak));
+
+        positions
+    }
+
+    /// Merge this proof for the leaf at `pos_a` and `other` for the leaf at `pos_b` into
+    /// a single batch proof.
+    ///
+    /// Both proofs have to be for the same MMR size, otherwise [`Error::MmrSizeMismatch`]
+    /// is returned. Hashes shared by both proofs are included only once. Note that a
+    /// proof bags all peaks lower than the peak of the proven leaf into a single hash.
+    /// Hence, merging fails with [`Error::MissingProofNode`], if there is more than one
+    /// peak lower than the peaks of both leaves.
+    pub fn merge(self, other: MerkleProof, pos_a: u64, pos_b: u64) -> Result<BatchProof, Error> {
+        if self.mmr_size != other.mmr_size {
+            return Err(Error::MmrSizeMismatch(self.mmr_size, other.mmr_size));
+        }
+
+        let mut nodes = self.nodes(pos_a);
+        nodes.extend(other.nodes(pos_b));
+
+        let mut leaves = vec![(pos_a, Hash::default()), (pos_b, Hash::default())];
+        leaves.sort_unstable_by_key(|&(pos, _)| pos);
+
+        let mut path = vec![];
+
+        // only the positions of the requested nodes matter, not the leaf hashes
+        batch::fold_peaks(self.mmr_size, &leaves, IndexEncoding::default(), |pos| {
+            let hash = nodes
+                .iter()
+                .find(|n| n.0 == pos)
+                .map(|n| n.1)
+                .ok_or(Error::MissingProofNode(pos))?;
+
+            path.push(hash);
+            Ok(hash)
+        })?;
+
+        Ok(BatchProof {
+            mmr_size: self.mmr_size,
+            path,
+        })
+    }
+
+    /// Return `(pos, hash)` tuples for all path hashes of a proof for the leaf at `pos`,
+    /// which represent a single node, i.e. excluding more than one bagged lower peak.
+    fn nodes(&self, pos: u64) -> Vec<(u64, Hash)> {
+        let siblings = utils::family_path(pos, self.mmr_size).len();
+        // the peak `pos` belongs to, followed by at least two lower peaks
+        let bagged = utils::peaks(self.mmr_size)
+            .into_iter()
+            .filter(|&p| p >= pos)
+            .count()
+            > 2;
+
+        self.positions(pos)
+            .into_iter()
+            .zip(self.path.iter().cloned())
+            .enumerate()
+            .filter(|&(i, _)| !(bagged && i == siblings))
+            .map(|(_, n)| n)
+            .collect()
+    }
+
+    /// Return the left / right decisions the verifier makes for a leaf at position `pos`.
+    ///
+    /// For each fold step, a tuple of the form `(position, is_left)` is returned, where
+    /// `is_left` indicates whether the node at `position` is the left child of its
+    /// parent. The decisions do not depend on the leaf value, only on its position.
+    pub fn trace(&self, pos: u64) -> Vec<(u64, bool)> {
+        let peaks = utils::peaks(self.mmr_size);
+        let mut pos = pos;
+
+        self.path
+            .iter()
+            .map(|_| {
+                let (parent_pos, is_left) = fold_step(pos, self.mmr_size, &peaks);
+                let step = (pos, is_left);
+                pos = parent_pos;
+                step
+            })
+            .collect()
     }
 
     fn do_verify(
+        &self,
+        root: Hash,
+        elem_hash: Hash,
+        pos: u64,
+        peaks: &[u64],
+        encoding: IndexEncoding,
+    ) -> Result<bool, Error> {
+        let hash = encoding.hash_with_index(node_index(pos, self.mmr_size), &elem_hash);
+
+        self.fold_node(root, hash, pos, peaks, encoding)
+    }
+
+    /// Fold the node `hash` at position `pos` with the proof path up to `root`.
+    ///
+    /// Folding is done iteratively, one path hash at a time, i.e. stack usage does not
+    /// depend on the proof length.
+    fn fold_node(
+        &self,
+        root: Hash,
+        hash: Hash,
+        pos: u64,
+        peaks: &[u64],
+        encoding: IndexEncoding,
+    ) -> Result<bool, Error> {
+        let hash = self.fold_root(hash, pos, peaks, encoding);
+
+        if root.ct_eq(&hash) {
+            Ok(true)
+        } else {
+            Err(Error::InvalidRootHash(hash, root))
+        }
+    }
+
+    /// Return the root hash implied by folding the node `hash` at position `pos` with
+    /// the proof path.
+    pub(crate) fn fold_root(
+        &self,
+        hash: Hash,
+        pos: u64,
+        peaks: &[u64],
+        encoding: IndexEncoding,
+    ) -> Hash {
+        let mut hash = hash;
+        let mut pos = pos;
+
+        for sibling in &self.path {
+            let (parent_pos, is_left) = fold_step(pos, self.mmr_size, peaks);
+
+            let parent = if is_left {
+                (hash, *sibling)
+            } else {
+                (*sibling, hash)
+            };
+
+            hash = encoding.hash_with_index(node_index(parent_pos, self.mmr_size), &parent.hash());
+            pos = parent_pos;
+        }
+
+        hash
+    }
+
+    /// Recursive proof verification, as [`MerkleProof::verify`] did before folding the
+    /// proof path became iterative. Kept for comparing results in tests only.
+    #[cfg(test)]
+    fn verify_recursive(
         &mut self,
         root: Hash,
-        elem: Vec<u8>,
+        elem_hash: Hash,
         pos: u64,
         peaks: &[u64],
+        encoding: IndexEncoding,
     ) -> Result<bool, Error> {
         let hash = if pos > self.mmr_size {
-            hash_with_index(self.mmr_size, &elem.hash())
+            encoding.hash_with_index(self.mmr_size, &elem_hash)
         } else {
-            hash_with_index(pos - 1, &elem.hash())
+            encoding.hash_with_index(pos - 1, &elem_hash)
         };
 
         // MMR has only a single node
@@ -74,23 +694,186 @@ impl MerkleProof {
         let sibling = self.path.remove(0);
         let (parent_pos, sibling_pos) = utils::family(pos);
 
-        if let Ok(x) = peaks.binary_search(&pos) {
-            let parent = if x == peaks.len() - 1 {
+        let parent = if let Ok(x) = peaks.binary_search(&pos) {
+            if x == peaks.len() - 1 {
                 (sibling, hash)
             } else {
                 (hash, sibling)
-            };
-            self.verify(root, &parent, parent_pos)
-        } else if parent_pos > self.mmr_size {
-            let parent = (sibling, hash);
-            self.verify(root, &parent, parent_pos)
+            }
+        } else if parent_pos > self.mmr_size || utils::is_left(sibling_pos) {
+            (sibling, hash)
         } else {
-            let parent = if utils::is_left(sibling_pos) {
-                (sibling, hash)
-            } else {
-                (hash, sibling)
-            };
-            self.verify(root, &parent, parent_pos)
+            (hash, sibling)
+        };
+
+        self.verify_recursive(root, parent.hash(), parent_pos, peaks, encoding)
+    }
+}
+
+/// Return the index a node at position `pos` is salted with.
+///
+/// Bagged peaks are positioned beyond the MMR size and salted with the MMR size.
+fn node_index(pos: u64, mmr_size: u64) -> u64 {
+    if pos > mmr_size {
+        mmr_size
+    } else {
+        pos.saturating_sub(1)
+    }
+}
+
+/// A compact summary of a [`MerkleProof`], e.g. for logging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofSummary {
+    /// Size of the MMR the proof has been generated for
+    pub mmr_size: u64,
+    /// Number of proof path hashes
+    pub num_siblings: usize,
+    /// Size of the SCALE encoded proof in bytes
+    pub total_bytes: usize,
+}
+
+/// A self-describing MMR membership proof, carrying the proven leaf along with its
+/// position.
+#[derive(Clone, Debug, PartialEq, Encode, Decode, TypeInfo)]
+pub struct FullProof<T> {
+    /// membership proof for `leaf`
+    pub proof: MerkleProof,
+    /// position of `leaf`
+    pub pos: u64,
+    /// the proven leaf itself
+    pub leaf: T,
+}
+
+impl<T> FullProof<T>
+where
+    T: Clone + Encode,
+{
+    pub fn new(proof: MerkleProof, pos: u64, leaf: T) -> Self {
+        FullProof { proof, pos, leaf }
+    }
+
+    /// Verify that the embedded leaf is a MMR leaf at the embedded position given
+    /// the root hash `root`. See [`MerkleProof::verify`].
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn verify(&self, root: Hash) -> Result<bool, Error> {
+        self.proof.verify(root, &self.leaf, self.pos)
+    }
+}
+
+/// A streaming MMR membership proof verifier.
+///
+/// Proof path hashes are fed one at a time, in proof path order, without the need to
+/// buffer the whole path. Each step mirrors [`MerkleProof::verify`].
+///
+/// Node indices are mixed into node hashes little-endian, unless the verifier has been
+/// created using [`ProofVerifier::with_index_encoding`].
+#[derive(Clone, Debug)]
+pub struct ProofVerifier {
+    root: Hash,
+    mmr_size: u64,
+    peaks: Vec<u64>,
+    encoding: IndexEncoding,
+    // number of proof path hashes fed so far
+    fed: usize,
+    // position of the node `hash` belongs to
+    pos: u64,
+    // hash of the node at `pos`
+    hash: Hash,
+}
+
+impl ProofVerifier {
+    /// Return a verifier for `elem` being a MMR node at position `pos`, given the root
+    /// hash `root` of a MMR with `mmr_size` nodes.
+    pub fn new<T>(root: Hash, elem: &T, pos: u64, mmr_size: u64) -> Self
+    where
+        T: Clone + Encode,
+    {
+        ProofVerifier::with_index_encoding(root, elem, pos, mmr_size, IndexEncoding::default())
+    }
+
+    /// Return a verifier like [`ProofVerifier::new`], for a MMR mixing node indices into
+    /// node hashes using `encoding`.
+    pub fn with_index_encoding<T>(
+        root: Hash,
+        elem: &T,
+        pos: u64,
+        mmr_size: u64,
+        encoding: IndexEncoding,
+    ) -> Self
+    where
+        T: Clone + Encode,
+    {
+        let mut verifier = ProofVerifier {
+            root,
+            mmr_size,
+            peaks: utils::peaks(mmr_size),
+            encoding,
+            fed: 0,
+            pos,
+            hash: Hash::default(),
+        };
+
+        verifier.hash = verifier.node_hash(&elem.encode().hash());
+        verifier
+    }
+
+    /// Fold the next proof path hash `sibling` into the current node hash.
+    ///
+    /// Feeding more than [`MerkleProof::max_expected_len`] hashes is rejected with
+    /// [`Error::ProofTooLong`], the current node hash is left untouched in that case.
+    pub fn feed(&mut self, sibling: Hash) -> Result<(), Error> {
+        let max = MerkleProof::max_expected_len(self.mmr_size);
+
+        if self.fed >= max {
+            return Err(Error::ProofTooLong(self.fed as u64 + 1, max as u64));
         }
+
+        let (parent_pos, is_left) = fold_step(self.pos, self.mmr_size, &self.peaks);
+
+        let parent = if is_left {
+            Hash::combine(&self.hash, &sibling)
+        } else {
+            Hash::combine(&sibling, &self.hash)
+        };
+
+        self.fed += 1;
+        self.pos = parent_pos;
+        self.hash = self.node_hash(&parent);
+
+        Ok(())
+    }
+
+    /// Finish verification by comparing the folded hash against the root hash.
+    #[must_use = "a failed verification is reported as an error"]
+    pub fn finish(self) -> Result<bool, Error> {
+        if !self.hash.ct_eq(&self.root) {
+            return Err(Error::InvalidRootHash(self.hash, self.root));
+        }
+
+        Ok(true)
+    }
+
+    /// Salt `hash` with the index of the current node, see [`MerkleProof::verify`].
+    fn node_hash(&self, hash: &Hash) -> Hash {
+        self.encoding
+            .hash_with_index(node_index(self.pos, self.mmr_size), hash)
     }
 }
+
+/// Return the parent position for the node at `pos` and whether the node at `pos` is
+/// the left child of that parent, as seen by the proof verifier.
+///
+/// `peaks` are the peak positions for a MMR with `mmr_size` nodes.
+fn fold_step(pos: u64, mmr_size: u64, peaks: &[u64]) -> (u64, bool) {
+    let (parent_pos, sibling_pos) = utils::family(pos);
+
+    let is_left = if let Ok(x) = peaks.binary_search(&pos) {
+        x != peaks.len() - 1
+    } else if parent_pos > mmr_size {
+        false
+    } else {
+        !utils::is_left(sibling_pos)
+    };
+
+    (parent_pos, is_left)
+}
@@ -16,14 +16,16 @@
 
 //! Merkle Proof unit tests
 
-use crate::{MerkleMountainRange, VecStore};
+use codec::Encode;
+
+use crate::{utils, Error, Hashable, IndexEncoding, MerkleMountainRange, VecStore};
 
 type E = Vec<u8>;
 
 #[test]
 fn minimal_proof_works() {
     let s = VecStore::<E>::new();
-    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(s);
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
 
     let node = vec![42u8];
     let size = mmr.append(&node).unwrap();
@@ -35,3 +37,42 @@ fn minimal_proof_works() {
     let root = mmr.hash(size).unwrap();
     assert!(proof.verify(root, &node, size).unwrap());
 }
+
+#[test]
+fn verify_deep_proof_works() -> Result<(), Error> {
+    let s = VecStore::<u32>::new();
+    let mut mmr = MerkleMountainRange::<u32, VecStore<u32>>::new(0, s);
+
+    // a single peak of height 12, followed by peaks of height 11 to 0
+    for i in 0..8191u32 {
+        mmr.append(&i)?;
+    }
+
+    let root = mmr.root()?;
+    let encoding = IndexEncoding::default();
+
+    for &elem in [0u32, 2047, 4095, 4096, 8190].iter() {
+        let pos = utils::mmr_size(u64::from(elem)) + 1;
+        let proof = mmr.proof(pos)?;
+        let peaks = utils::peaks(proof.mmr_size);
+
+        let want =
+            proof
+                .clone()
+                .verify_recursive(root, elem.encode().hash(), pos, &peaks, encoding);
+
+        assert_eq!(want, proof.verify(root, &elem, pos));
+        assert_eq!(Ok(true), want);
+
+        let other = elem + 1;
+        let want =
+            proof
+                .clone()
+                .verify_recursive(root, other.encode().hash(), pos, &peaks, encoding);
+
+        assert_eq!(want, proof.verify(root, &other, pos));
+        assert!(matches!(want, Err(Error::InvalidRootHash(..))));
+    }
+
+    Ok(())
+}
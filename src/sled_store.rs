@@ -0,0 +1,167 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle-Mountain-Range storage backed by a `sled` embedded database
+
+use core::marker::PhantomData;
+
+use codec::{Decode, DecodeAll, Encode};
+use sled::transaction::{TransactionError, Transactional};
+
+use crate::{Error, Hash, Result, Store};
+
+/// A store persisting MMR nodes in a `sled` database.
+///
+/// Hashes are kept in the `hashes` tree, keyed by their big-endian `u64` index. The
+/// SCALE encoded elements are kept in the `data` tree, keyed by their big-endian
+/// `u64` leaf index.
+pub struct SledStore<T> {
+    hashes: sled::Tree,
+    data: sled::Tree,
+    _marker: PhantomData<T>,
+}
+
+impl<T> SledStore<T> {
+    /// Open a store within `db`, creating it if necessary.
+    pub fn open(db: &sled::Db) -> Result<Self> {
+        Ok(SledStore {
+            hashes: db.open_tree("hashes").map_err(io_error)?,
+            data: db.open_tree("data").map_err(io_error)?,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Flush all pending writes to disk.
+    pub fn flush(&self) -> Result<()> {
+        self.hashes.flush().map_err(io_error)?;
+        self.data.flush().map_err(io_error)?;
+
+        Ok(())
+    }
+}
+
+impl<T> Store<T> for SledStore<T>
+where
+    T: Clone + Decode + Encode,
+{
+    fn hash_at(&self, index: u64) -> Result<Hash> {
+        self.hashes
+            .get(index.to_be_bytes())
+            .map_err(io_error)?
+            .ok_or(Error::MissingHashAtIndex(index))
+            .and_then(|h| {
+                if h.len() != Hash::LEN {
+                    return Err(Error::StoreIo("invalid hash length".to_string()));
+                }
+
+                Ok(Hash::from_vec(&h))
+            })
+    }
+
+    fn contains(&self, index: u64) -> bool {
+        self.hashes
+            .contains_key(index.to_be_bytes())
+            .unwrap_or_default()
+    }
+
+    /// The element and all hashes are written within a single transaction spanning
+    /// both trees, i.e. either all of them are written or none.
+    fn append(&mut self, elem: &T, hashes: &[Hash]) -> Result<()> {
+        let leaf = next_key(&self.data)?;
+        let start = next_key(&self.hashes)?;
+        let elem = elem.encode();
+
+        (&self.data, &self.hashes)
+            .transaction(|(data, tree)| {
+                data.insert(&leaf.to_be_bytes()[..], &elem[..])?;
+
+                for (idx, hash) in (start..).zip(hashes.iter()) {
+                    tree.insert(&idx.to_be_bytes()[..], &hash.0[..])?;
+                }
+
+                Ok(())
+            })
+            .map_err(|err: TransactionError| match err {
+                TransactionError::Abort(err) | TransactionError::Storage(err) => io_error(err),
+            })
+    }
+
+    /// The last element and the last `nodes` hashes are removed within a single
+    /// transaction spanning both trees.
+    fn undo_append(&mut self, nodes: u64) -> Result<()> {
+        let leaves = next_key(&self.data)?;
+        let len = next_key(&self.hashes)?;
+
+        if leaves == 0 || nodes > len {
+            return Err(Error::MissingHashAtIndex(0));
+        }
+
+        (&self.data, &self.hashes)
+            .transaction(|(data, tree)| {
+                data.remove(&(leaves - 1).to_be_bytes()[..])?;
+
+                for idx in len - nodes..len {
+                    tree.remove(&idx.to_be_bytes()[..])?;
+                }
+
+                Ok(())
+            })
+            .map_err(|err: TransactionError| match err {
+                TransactionError::Abort(err) | TransactionError::Storage(err) => io_error(err),
+            })
+    }
+
+    /// Indices are dense, hence the number of nodes is the last index plus one.
+    ///
+    /// A database error is reported as `u64::MAX`, which is never a valid length for
+    /// a MMR being appended to. Hence, any append fails with [`Error::StoreDesync`]
+    /// instead of silently treating the store as empty.
+    fn len(&self) -> u64 {
+        next_key(&self.hashes).unwrap_or(u64::MAX)
+    }
+
+    fn leaf_data(&self) -> Result<Vec<T>> {
+        self.data
+            .iter()
+            .values()
+            .map(|v| {
+                let v = v.map_err(io_error)?;
+                T::decode_all(&mut &v[..]).map_err(|_| Error::InvalidLeafEncoding)
+            })
+            .collect()
+    }
+}
+
+/// Return the key following the last key in `tree`, i.e. the number of entries for
+/// a tree with dense big-endian `u64` keys.
+fn next_key(tree: &sled::Tree) -> Result<u64> {
+    let last = tree.last().map_err(io_error)?;
+
+    last.map_or(Ok(0), |(k, _)| {
+        let mut key = [0u8; 8];
+
+        if k.len() != key.len() {
+            return Err(Error::StoreIo("invalid key length".to_string()));
+        }
+
+        key.copy_from_slice(&k);
+        Ok(u64::from_be_bytes(key) + 1)
+    })
+}
+
+fn io_error(err: sled::Error) -> Error {
+    Error::StoreIo(err.to_string())
+}
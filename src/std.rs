@@ -14,8 +14,40 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::string::String;
 use std::vec;
 use std::vec::Vec;
 
 impl std::error::Error for crate::Error {}
+
+/// Convert an MMR error into an [`std::io::Error`], keeping the MMR error as its source.
+///
+/// The [`std::io::ErrorKind`] is chosen as follows:
+///
+/// - [`Error::StoreIo`] maps to [`std::io::ErrorKind::Other`],
+///   since the kind of the underlying store error is not retained.
+/// - Encoding errors, i.e. `InvalidHexString`, `InvalidLeafEncoding`,
+///   `InvalidPeaksEncoding` and `InvalidProofEncoding`, map to
+///   [`std::io::ErrorKind::InvalidData`].
+/// - Unavailable nodes or data, i.e. `DataNotRetained`, `MissingHashAtIndex` and
+///   `Pruned`, map to [`std::io::ErrorKind::NotFound`].
+/// - All other errors map to [`std::io::ErrorKind::InvalidInput`].
+impl From<crate::Error> for std::io::Error {
+    fn from(e: crate::Error) -> Self {
+        use crate::Error::*;
+        use std::io::ErrorKind;
+
+        let kind = match e {
+            StoreIo(_) => ErrorKind::Other,
+            InvalidHexString(_)
+            | InvalidLeafEncoding
+            | InvalidPeaksEncoding
+            | InvalidProofEncoding => ErrorKind::InvalidData,
+            DataNotRetained | MissingHashAtIndex(_) | Pruned(_) => ErrorKind::NotFound,
+            _ => ErrorKind::InvalidInput,
+        };
+
+        std::io::Error::new(kind, e)
+    }
+}
@@ -16,9 +16,11 @@
 
 //! Merkle-Mountain-Range storage
 
-use codec::{Decode, Encode};
+use core::{convert::TryFrom, marker::PhantomData};
 
-use crate::{vec, Error, Hash, Result, Vec};
+use codec::{Decode, DecodeAll, Encode, Input};
+
+use crate::{utils, vec, Error, Hash, Result, Vec};
 
 #[cfg(test)]
 #[path = "store_tests.rs"]
@@ -30,14 +32,181 @@ where
 {
     fn hash_at(&self, index: u64) -> Result<Hash>;
 
+    /// Return the hashes at all `indices`, in the same order.
+    ///
+    /// Stores with a high per-request overhead, e.g. remote stores, should override
+    /// this. The default implementation calls [`Store::hash_at`] for each index.
+    fn get_many(&self, indices: &[u64]) -> Result<Vec<Hash>> {
+        indices.iter().map(|&idx| self.hash_at(idx)).collect()
+    }
+
+    /// Return the peak hashes of a MMR with `size` nodes, left to right.
+    ///
+    /// Stores able to track peaks cheaply should override this. The default
+    /// implementation calls [`Store::hash_at`] for each peak.
+    fn peak_hashes(&self, size: u64) -> Result<Vec<Hash>> {
+        utils::peaks(size)
+            .into_iter()
+            .map(|p| self.hash_at(p - 1))
+            .collect()
+    }
+
+    /// Return `true`, if the store contains a hash at `index`.
+    ///
+    /// Stores should override this, if presence can be checked cheaper than fetching
+    /// the hash. The default implementation calls [`Store::hash_at`].
+    fn contains(&self, index: u64) -> bool {
+        self.hash_at(index).is_ok()
+    }
+
+    /// Append `elem` together with all new node `hashes`, i.e. the leaf hash followed
+    /// by any new parent hashes.
+    ///
+    /// [`MerkleMountainRange::append`](crate::MerkleMountainRange::append) calls this
+    /// exactly once per element, after all new hashes have been calculated. Stores are
+    /// expected to write `elem` and `hashes` atomically. If an error is returned, the
+    /// store has to be left unchanged, so that the append can be retried.
     fn append(&mut self, elem: &T, hashes: &[Hash]) -> Result<()>;
+
+    /// Undo the last [`Store::append`], i.e. remove the last element together with
+    /// the last `nodes` node hashes.
+    ///
+    /// This is used to roll back an append, which failed validation afterwards, see
+    /// [`MerkleMountainRange::with_paranoid`](crate::MerkleMountainRange::with_paranoid).
+    /// The default implementation does not support rolling back and returns
+    /// [`Error::StoreIo`].
+    fn undo_append(&mut self, _nodes: u64) -> Result<()> {
+        Err(Error::StoreIo("undo append not supported".to_string()))
+    }
+
+    /// Return the number of stored nodes, i.e. hashes.
+    fn len(&self) -> u64;
+
+    /// Return `true`, if the store does not contain any nodes.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reserve capacity for at least `leaves` more elements and `nodes` more nodes.
+    ///
+    /// This is merely a hint and must not panic, even for counts which can not be
+    /// allocated. The default implementation does nothing.
+    fn reserve(&mut self, _leaves: u64, _nodes: u64) {}
+
+    /// Prune all stored nodes, except the ones at indices in `retain`.
+    ///
+    /// Nodes appended afterwards are stored as usual. The default implementation
+    /// does not prune anything.
+    fn prune(&mut self, _retain: &[u64]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Release excess capacity, e.g. after pruning.
+    ///
+    /// The default implementation does nothing.
+    fn shrink_to_fit(&mut self) {}
+
+    /// Return all stored elements in append order.
+    ///
+    /// [`Error::DataNotRetained`] is returned, if the store does not retain elements.
+    /// This is what the default implementation does.
+    fn leaf_data(&self) -> Result<Vec<T>> {
+        Err(Error::DataNotRetained)
+    }
+}
+
+/// A store appending to an in-memory overlay on top of a read-only `base` store.
+///
+/// This allows to simulate appends without mutating `base`.
+pub(crate) struct OverlayStore<'a, S> {
+    base: &'a S,
+    hashes: Vec<Hash>,
 }
 
+impl<'a, S> OverlayStore<'a, S> {
+    pub(crate) fn new(base: &'a S) -> Self {
+        OverlayStore {
+            base,
+            hashes: vec![],
+        }
+    }
+}
+
+impl<'a, T, S> Store<T> for OverlayStore<'a, S>
+where
+    T: Clone + Decode + Encode,
+    S: Store<T>,
+{
+    fn hash_at(&self, index: u64) -> Result<Hash> {
+        let len = self.base.len();
+
+        if index < len {
+            return self.base.hash_at(index);
+        }
+
+        self.hashes
+            .get((index - len) as usize)
+            .cloned()
+            .ok_or(Error::MissingHashAtIndex(index))
+    }
+
+    fn append(&mut self, _elem: &T, hashes: &[Hash]) -> Result<()> {
+        self.hashes.extend_from_slice(hashes);
+
+        Ok(())
+    }
+
+    fn undo_append(&mut self, nodes: u64) -> Result<()> {
+        let len = self.hashes.len().saturating_sub(nodes as usize);
+        self.hashes.truncate(len);
+
+        Ok(())
+    }
+
+    fn len(&self) -> u64 {
+        self.base.len() + self.hashes.len() as u64
+    }
+}
+
+#[derive(Encode)]
 pub struct VecStore<T> {
     /// Optional store elements, `None` if only hashes are stored.
     pub data: Option<Vec<T>>,
     /// MMR hashes for both, laves and parents
     pub hashes: Vec<Hash>,
+    /// Number of pruned nodes, i.e. the index of the first node in `hashes`
+    pub pruned: u64,
+    /// Hashes retained while pruning, as `(index, hash)` tuples ordered by index
+    pub retained: Vec<(u64, Hash)>,
+}
+
+/// Decode the store, rejecting retained hashes which are not in strictly ascending
+/// index order, as well as a node count overflowing a `u64`.
+impl<T> Decode for VecStore<T>
+where
+    T: Decode,
+{
+    fn decode<I: Input>(input: &mut I) -> core::result::Result<Self, codec::Error> {
+        let data = Option::<Vec<T>>::decode(input)?;
+        let hashes = Vec::<Hash>::decode(input)?;
+        let pruned = u64::decode(input)?;
+        let retained = Vec::<(u64, Hash)>::decode(input)?;
+
+        if pruned.checked_add(hashes.len() as u64).is_none() {
+            return Err("invalid number of pruned nodes".into());
+        }
+
+        if retained.windows(2).any(|w| w[0].0 >= w[1].0) {
+            return Err("unsorted retained hashes".into());
+        }
+
+        Ok(VecStore {
+            data,
+            hashes,
+            pruned,
+            retained,
+        })
+    }
 }
 
 impl<T> Store<T> for VecStore<T>
@@ -45,12 +214,31 @@ where
     T: Clone + Decode + Encode,
 {
     fn hash_at(&self, index: u64) -> Result<Hash> {
+        if index < self.pruned {
+            return self
+                .retained
+                .binary_search_by_key(&index, |&(idx, _)| idx)
+                .map(|i| self.retained[i].1)
+                .map_err(|_| Error::Pruned(index));
+        }
+
         self.hashes
-            .get(index as usize)
+            .get((index - self.pruned) as usize)
             .cloned()
             .ok_or(Error::MissingHashAtIndex(index))
     }
 
+    fn contains(&self, index: u64) -> bool {
+        if index < self.pruned {
+            return self
+                .retained
+                .binary_search_by_key(&index, |&(idx, _)| idx)
+                .is_ok();
+        }
+
+        index < VecStore::len(self)
+    }
+
     fn append(&mut self, elem: &T, hashes: &[Hash]) -> Result<()> {
         if let Some(data) = &mut self.data {
             data.push(elem.clone());
@@ -60,6 +248,68 @@ where
 
         Ok(())
     }
+
+    /// Pruned nodes can not be removed, hence [`Error::Pruned`] is returned, if any
+    /// of the last `nodes` nodes has been pruned.
+    fn undo_append(&mut self, nodes: u64) -> Result<()> {
+        if nodes > self.hashes.len() as u64 {
+            return Err(Error::Pruned(VecStore::len(self).saturating_sub(nodes)));
+        }
+
+        if let Some(data) = &mut self.data {
+            data.pop();
+        }
+
+        self.hashes.truncate(self.hashes.len() - nodes as usize);
+
+        Ok(())
+    }
+
+    fn len(&self) -> u64 {
+        VecStore::len(self)
+    }
+
+    fn reserve(&mut self, leaves: u64, nodes: u64) {
+        // reserving is a hint only, hence allocation failures are ignored
+        if let Some(data) = &mut self.data {
+            let _ = data.try_reserve(usize::try_from(leaves).unwrap_or(usize::MAX));
+        }
+
+        let _ = self
+            .hashes
+            .try_reserve(usize::try_from(nodes).unwrap_or(usize::MAX));
+    }
+
+    fn prune(&mut self, retain: &[u64]) -> Result<()> {
+        let len = VecStore::len(self);
+
+        let mut retained = retain
+            .iter()
+            .map(|&idx| Store::<T>::hash_at(self, idx).map(|h| (idx, h)))
+            .collect::<Result<Vec<_>>>()?;
+
+        retained.sort_unstable_by_key(|&(idx, _)| idx);
+        retained.dedup_by_key(|&mut (idx, _)| idx);
+
+        self.retained = retained;
+        self.pruned = len;
+        self.hashes.clear();
+
+        Ok(())
+    }
+
+    fn shrink_to_fit(&mut self) {
+        if let Some(data) = &mut self.data {
+            data.shrink_to_fit();
+        }
+
+        self.hashes.shrink_to_fit();
+        self.retained.shrink_to_fit();
+    }
+
+    fn leaf_data(&self) -> Result<Vec<T>> {
+        self.data.clone().ok_or(Error::DataNotRetained)
+    }
 }
 
 impl<T> VecStore<T> {
@@ -67,6 +317,35 @@ impl<T> VecStore<T> {
         VecStore {
             data: Some(vec![]),
             hashes: vec![],
+            pruned: 0,
+            retained: vec![],
+        }
+    }
+
+    /// Return the number of nodes, including pruned ones.
+    pub fn len(&self) -> u64 {
+        self.pruned + self.hashes.len() as u64
+    }
+
+    /// Return `true`, if the store does not contain any nodes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return an iterator over all hashes, which have not been pruned.
+    ///
+    /// Hashes are yielded in index order, starting at index [`VecStore::pruned`].
+    pub fn iter_hashes(&self) -> impl Iterator<Item = &Hash> {
+        self.hashes.iter()
+    }
+
+    /// Return a new store with capacity for at least `capacity` nodes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        VecStore {
+            data: Some(vec![]),
+            hashes: Vec::with_capacity(capacity),
+            pruned: 0,
+            retained: vec![],
         }
     }
 }
@@ -76,3 +355,271 @@ impl<T> Default for VecStore<T> {
         Self::new()
     }
 }
+
+/// A store keeping all hashes in a single contiguous byte vector.
+///
+/// Each hash occupies [`Hash::LEN`] bytes, hence [`Store::hash_at`] is a plain slice
+/// copy without any per-node allocation. Pruning is not supported.
+#[derive(Encode, Decode)]
+pub struct PackedVecStore<T> {
+    /// Optional store elements, `None` if only hashes are stored.
+    pub data: Option<Vec<T>>,
+    /// Concatenated MMR hashes for both, leaves and parents
+    pub hashes: Vec<u8>,
+}
+
+impl<T> Store<T> for PackedVecStore<T>
+where
+    T: Clone + Decode + Encode,
+{
+    fn hash_at(&self, index: u64) -> Result<Hash> {
+        if index >= PackedVecStore::len(self) {
+            return Err(Error::MissingHashAtIndex(index));
+        }
+
+        let start = index as usize * Hash::LEN;
+
+        Ok(Hash::from_vec(&self.hashes[start..start + Hash::LEN]))
+    }
+
+    fn contains(&self, index: u64) -> bool {
+        index < PackedVecStore::len(self)
+    }
+
+    fn append(&mut self, elem: &T, hashes: &[Hash]) -> Result<()> {
+        if let Some(data) = &mut self.data {
+            data.push(elem.clone());
+        }
+
+        hashes
+            .iter()
+            .for_each(|h| self.hashes.extend_from_slice(&h.0));
+
+        Ok(())
+    }
+
+    fn undo_append(&mut self, nodes: u64) -> Result<()> {
+        if nodes > PackedVecStore::len(self) {
+            return Err(Error::MissingHashAtIndex(0));
+        }
+
+        if let Some(data) = &mut self.data {
+            data.pop();
+        }
+
+        self.hashes
+            .truncate(self.hashes.len() - nodes as usize * Hash::LEN);
+
+        Ok(())
+    }
+
+    fn len(&self) -> u64 {
+        PackedVecStore::len(self)
+    }
+
+    fn reserve(&mut self, leaves: u64, nodes: u64) {
+        // reserving is a hint only, hence allocation failures are ignored
+        if let Some(data) = &mut self.data {
+            let _ = data.try_reserve(usize::try_from(leaves).unwrap_or(usize::MAX));
+        }
+
+        let bytes = usize::try_from(nodes).map_or(usize::MAX, |n| n.saturating_mul(Hash::LEN));
+        let _ = self.hashes.try_reserve(bytes);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        if let Some(data) = &mut self.data {
+            data.shrink_to_fit();
+        }
+
+        self.hashes.shrink_to_fit();
+    }
+
+    fn leaf_data(&self) -> Result<Vec<T>> {
+        self.data.clone().ok_or(Error::DataNotRetained)
+    }
+}
+
+impl<T> PackedVecStore<T> {
+    pub fn new() -> Self {
+        PackedVecStore {
+            data: Some(vec![]),
+            hashes: vec![],
+        }
+    }
+
+    /// Return the number of nodes.
+    pub fn len(&self) -> u64 {
+        (self.hashes.len() / Hash::LEN) as u64
+    }
+
+    /// Return `true`, if the store does not contain any nodes.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+}
+
+impl<T> Default for PackedVecStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A store keeping SCALE encoded elements in a single byte vector.
+///
+/// The start offset of each encoded element is kept in a sidecar index. This allows
+/// random access to variable-size elements by leaf index in `O(1)`, see
+/// [`IndexedVecStore::data_at`].
+#[derive(Encode)]
+pub struct IndexedVecStore<T> {
+    /// Concatenated SCALE encoded store elements
+    pub data: Vec<u8>,
+    /// Start offset of each element in `data`, ordered by leaf index
+    pub data_offsets: Vec<u64>,
+    /// MMR hashes for both, leaves and parents
+    pub hashes: Vec<Hash>,
+    // make rustc happy
+    _marker: PhantomData<T>,
+}
+
+/// Decode the store, rejecting data offsets which are not in ascending order or
+/// point beyond the end of the element data.
+impl<T> Decode for IndexedVecStore<T> {
+    fn decode<I: Input>(input: &mut I) -> core::result::Result<Self, codec::Error> {
+        let data = Vec::<u8>::decode(input)?;
+        let data_offsets = Vec::<u64>::decode(input)?;
+        let hashes = Vec::<Hash>::decode(input)?;
+
+        let sorted = data_offsets.windows(2).all(|w| w[0] <= w[1]);
+        let in_range = data_offsets
+            .last()
+            .is_none_or(|&last| last <= data.len() as u64);
+
+        if !sorted || !in_range {
+            return Err("invalid data offsets".into());
+        }
+
+        Ok(IndexedVecStore {
+            data,
+            data_offsets,
+            hashes,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T> Store<T> for IndexedVecStore<T>
+where
+    T: Clone + Decode + Encode,
+{
+    fn hash_at(&self, index: u64) -> Result<Hash> {
+        self.hashes
+            .get(index as usize)
+            .cloned()
+            .ok_or(Error::MissingHashAtIndex(index))
+    }
+
+    fn contains(&self, index: u64) -> bool {
+        index < IndexedVecStore::len(self)
+    }
+
+    fn append(&mut self, elem: &T, hashes: &[Hash]) -> Result<()> {
+        self.data_offsets.push(self.data.len() as u64);
+        elem.encode_to(&mut self.data);
+        self.hashes.extend_from_slice(hashes);
+
+        Ok(())
+    }
+
+    fn undo_append(&mut self, nodes: u64) -> Result<()> {
+        if nodes > IndexedVecStore::len(self) {
+            return Err(Error::MissingHashAtIndex(0));
+        }
+
+        if let Some(start) = self.data_offsets.pop() {
+            self.data.truncate(start as usize);
+        }
+
+        self.hashes.truncate(self.hashes.len() - nodes as usize);
+
+        Ok(())
+    }
+
+    fn len(&self) -> u64 {
+        IndexedVecStore::len(self)
+    }
+
+    fn reserve(&mut self, leaves: u64, nodes: u64) {
+        // reserving is a hint only, hence allocation failures are ignored
+        let _ = self
+            .data_offsets
+            .try_reserve(usize::try_from(leaves).unwrap_or(usize::MAX));
+        let _ = self
+            .hashes
+            .try_reserve(usize::try_from(nodes).unwrap_or(usize::MAX));
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+        self.data_offsets.shrink_to_fit();
+        self.hashes.shrink_to_fit();
+    }
+
+    fn leaf_data(&self) -> Result<Vec<T>> {
+        (0..self.data_offsets.len() as u64)
+            .map(|i| self.data_at(i))
+            .collect()
+    }
+}
+
+impl<T> IndexedVecStore<T> {
+    pub fn new() -> Self {
+        IndexedVecStore {
+            data: vec![],
+            data_offsets: vec![],
+            hashes: vec![],
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the number of nodes.
+    pub fn len(&self) -> u64 {
+        self.hashes.len() as u64
+    }
+
+    /// Return `true`, if the store does not contain any nodes.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Return the element with `0` based leaf index `leaf_index`.
+    pub fn data_at(&self, leaf_index: u64) -> Result<T>
+    where
+        T: Decode,
+    {
+        let leaves = self.data_offsets.len() as u64;
+
+        if leaf_index >= leaves {
+            return Err(Error::LeafIndexOutOfRange(leaf_index, leaves));
+        }
+
+        let start = self.data_offsets[leaf_index as usize] as usize;
+        let end = self
+            .data_offsets
+            .get(leaf_index as usize + 1)
+            .map_or(self.data.len(), |&end| end as usize);
+
+        // offsets are public and hence not trusted
+        if start > end || end > self.data.len() {
+            return Err(Error::InvalidLeafEncoding);
+        }
+
+        T::decode_all(&mut &self.data[start..end]).map_err(|_| Error::InvalidLeafEncoding)
+    }
+}
+
+impl<T> Default for IndexedVecStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
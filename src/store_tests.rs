@@ -16,12 +16,14 @@
 
 //! Merkle-Mountain-Range storage unit tests
 
-use super::{Error, Store, VecStore};
+use codec::{Decode, Encode};
+
+use super::{Error, IndexedVecStore, PackedVecStore, Store, VecStore};
 use crate::Hashable;
 
 #[test]
 fn append_works() {
-    #![allow(clippy::unit_cmp)]
+    #![allow(clippy::unit_cmp, clippy::let_unit_value)]
 
     let elem = vec![0u8; 10];
     let h = elem.hash();
@@ -96,3 +98,243 @@ fn hash_at_fails() {
 
     assert_eq!(want, got);
 }
+
+#[test]
+fn with_capacity_works() {
+    let mut store = VecStore::<Vec<u8>>::with_capacity(10);
+
+    assert!(store.hashes.capacity() >= 10);
+    assert!(store.hashes.is_empty());
+
+    store.reserve(10, 20);
+
+    assert!(store.hashes.capacity() >= 20);
+    assert!(store.data.unwrap().capacity() >= 10);
+}
+
+#[test]
+fn len_works() {
+    let mut store = VecStore::<Vec<u8>>::new();
+
+    assert_eq!(0, store.len());
+    assert!(store.is_empty());
+
+    let elem = vec![0u8; 10];
+    let _ = store.append(&elem, &[elem.hash(), elem.hash()]);
+
+    assert_eq!(2, store.len());
+    assert!(!store.is_empty());
+}
+
+#[test]
+fn contains_works() {
+    let mut store = VecStore::<Vec<u8>>::new();
+
+    assert!(!store.contains(0));
+
+    let hashes = (0..5u64).map(|i| i.hash()).collect::<Vec<_>>();
+    let _ = store.append(&vec![0u8], &hashes);
+
+    assert!(store.contains(0));
+    assert!(store.contains(4));
+    assert!(!store.contains(5));
+
+    let _ = store.prune(&[1, 3]);
+
+    assert!(!store.contains(0));
+    assert!(store.contains(1));
+    assert!(store.contains(3));
+    assert!(!store.contains(4));
+    assert!(!store.contains(5));
+}
+
+#[test]
+fn prune_works() {
+    let mut store = VecStore::<Vec<u8>>::new();
+    let hashes = (0..5u64).map(|i| i.hash()).collect::<Vec<_>>();
+
+    let _ = store.append(&vec![0u8; 10], &hashes);
+    let _ = store.prune(&[3, 1]);
+
+    assert_eq!(5, store.len());
+    assert_eq!(Err(Error::Pruned(0)), store.hash_at(0));
+    assert_eq!(Ok(hashes[1]), store.hash_at(1));
+    assert_eq!(Err(Error::Pruned(2)), store.hash_at(2));
+    assert_eq!(Ok(hashes[3]), store.hash_at(3));
+    assert_eq!(Err(Error::MissingHashAtIndex(5)), store.hash_at(5));
+
+    let h = 5u64.hash();
+    let _ = store.append(&vec![1u8; 10], &[h]);
+
+    assert_eq!(6, store.len());
+    assert_eq!(Ok(h), store.hash_at(5));
+}
+
+#[test]
+fn iter_hashes_works() {
+    let mut store = VecStore::<Vec<u8>>::new();
+
+    assert_eq!(0, store.iter_hashes().count());
+
+    let hashes = (0..5u64).map(|i| i.hash()).collect::<Vec<_>>();
+    let _ = store.append(&vec![0u8; 10], &hashes);
+
+    assert_eq!(5, store.len());
+    assert!(store.iter_hashes().eq(store.hashes.iter()));
+
+    let _ = store.prune(&[2]);
+    let h = 5u64.hash();
+    let _ = store.append(&vec![1u8; 10], &[h]);
+
+    assert_eq!(6, store.len());
+    assert_eq!(vec![&h], store.iter_hashes().collect::<Vec<_>>());
+}
+
+#[test]
+fn indexed_vec_store_works() {
+    let mut store = IndexedVecStore::<Vec<u8>>::new();
+    let leaves = vec![vec![1u8], vec![], vec![2u8; 100], vec![3u8; 7]];
+
+    for (i, leaf) in leaves.iter().enumerate() {
+        let _ = store.append(leaf, &[(i as u64).hash()]);
+    }
+
+    assert_eq!(4, store.len());
+    assert_eq!(leaves[2], store.data_at(2).unwrap());
+    assert_eq!(leaves[0], store.data_at(0).unwrap());
+    assert_eq!(leaves[3], store.data_at(3).unwrap());
+    assert_eq!(leaves[1], store.data_at(1).unwrap());
+    assert_eq!(leaves, store.leaf_data().unwrap());
+
+    assert_eq!(
+        Error::LeafIndexOutOfRange(4, 4),
+        store.data_at(4).err().unwrap()
+    );
+}
+
+#[test]
+fn indexed_vec_store_invalid_offsets() {
+    let mut store = IndexedVecStore::<Vec<u8>>::new();
+    let leaves = vec![vec![1u8], vec![2u8; 10], vec![3u8; 7]];
+
+    for (i, leaf) in leaves.iter().enumerate() {
+        let _ = store.append(leaf, &[(i as u64).hash()]);
+    }
+
+    let decoded = IndexedVecStore::<Vec<u8>>::decode(&mut &store.encode()[..]).unwrap();
+    assert_eq!(leaves, decoded.leaf_data().unwrap());
+
+    // offsets not in ascending order
+    let mut corrupt = IndexedVecStore::<Vec<u8>>::new();
+    corrupt.data = store.data.clone();
+    corrupt.data_offsets = vec![0, 12, 2];
+    corrupt.hashes = store.hashes.clone();
+
+    assert!(IndexedVecStore::<Vec<u8>>::decode(&mut &corrupt.encode()[..]).is_err());
+    assert_eq!(Err(Error::InvalidLeafEncoding), corrupt.data_at(1));
+
+    // offset beyond the end of the data
+    corrupt.data_offsets = vec![0, 2, 100];
+
+    assert!(IndexedVecStore::<Vec<u8>>::decode(&mut &corrupt.encode()[..]).is_err());
+    assert_eq!(Err(Error::InvalidLeafEncoding), corrupt.data_at(1));
+    assert_eq!(Err(Error::InvalidLeafEncoding), corrupt.data_at(2));
+}
+
+#[test]
+fn vec_store_invalid_decode() {
+    let mut store = VecStore::<Vec<u8>>::new();
+
+    for i in 0..7u64 {
+        let _ = store.append(&vec![i as u8], &[i.hash()]);
+    }
+
+    store.prune(&[1, 4]).unwrap();
+
+    let decoded = VecStore::<Vec<u8>>::decode(&mut &store.encode()[..]).unwrap();
+    assert_eq!(store.retained, decoded.retained);
+    assert_eq!(store.hash_at(4), decoded.hash_at(4));
+
+    // retained hashes not in ascending order
+    let mut corrupt = VecStore::<Vec<u8>>::decode(&mut &store.encode()[..]).unwrap();
+    corrupt.retained.reverse();
+
+    assert!(VecStore::<Vec<u8>>::decode(&mut &corrupt.encode()[..]).is_err());
+
+    // duplicate retained hash index
+    corrupt.retained = vec![(1, 1u64.hash()), (1, 1u64.hash())];
+
+    assert!(VecStore::<Vec<u8>>::decode(&mut &corrupt.encode()[..]).is_err());
+
+    // number of nodes overflowing a u64
+    corrupt.retained = vec![];
+    corrupt.hashes = vec![7u64.hash()];
+    corrupt.pruned = u64::MAX;
+
+    assert!(VecStore::<Vec<u8>>::decode(&mut &corrupt.encode()[..]).is_err());
+}
+
+#[test]
+fn undo_append_works() {
+    let a = vec![1u8];
+    let b = vec![2u8, 3];
+    let h = [a.hash(), b.hash(), (a.hash(), b.hash()).hash()];
+
+    let mut store = VecStore::<Vec<u8>>::new();
+    store.append(&a, &h[..1]).unwrap();
+    store.append(&b, &h[1..]).unwrap();
+
+    assert_eq!(Ok(()), Store::undo_append(&mut store, 2));
+    assert_eq!(1, store.len());
+    assert_eq!(Some(vec![a.clone()]), store.data);
+
+    let mut store = PackedVecStore::<Vec<u8>>::new();
+    store.append(&a, &h[..1]).unwrap();
+    store.append(&b, &h[1..]).unwrap();
+
+    assert_eq!(Ok(()), Store::undo_append(&mut store, 2));
+    assert_eq!(1, store.len());
+    assert_eq!(Ok(h[0]), store.hash_at(0));
+    assert_eq!(Some(vec![a.clone()]), store.data);
+
+    let mut store = IndexedVecStore::<Vec<u8>>::new();
+    store.append(&a, &h[..1]).unwrap();
+    store.append(&b, &h[1..]).unwrap();
+
+    assert_eq!(Ok(()), Store::undo_append(&mut store, 2));
+    assert_eq!(1, store.len());
+    assert_eq!(Ok(vec![a.clone()]), store.leaf_data());
+    assert_eq!(a.encode(), store.data);
+}
+
+#[test]
+fn undo_append_pruned_fails() {
+    let a = vec![1u8];
+    let mut store = VecStore::<Vec<u8>>::new();
+
+    store.append(&a, &[a.hash()]).unwrap();
+    Store::prune(&mut store, &[0]).unwrap();
+
+    assert_eq!(Err(Error::Pruned(0)), Store::undo_append(&mut store, 1));
+    assert_eq!(1, store.len());
+}
+
+#[test]
+fn reserve_overflow_works() {
+    let elem = vec![0u8];
+
+    let mut store = VecStore::<Vec<u8>>::new();
+    Store::reserve(&mut store, u64::MAX, u64::MAX);
+    store.append(&elem, &[elem.hash()]).unwrap();
+    assert_eq!(1, store.len());
+
+    let mut store = PackedVecStore::<Vec<u8>>::new();
+    Store::reserve(&mut store, u64::MAX, u64::MAX);
+    store.append(&elem, &[elem.hash()]).unwrap();
+    assert_eq!(1, store.len());
+
+    let mut store = IndexedVecStore::<Vec<u8>>::new();
+    Store::reserve(&mut store, u64::MAX, u64::MAX);
+    store.append(&elem, &[elem.hash()]).unwrap();
+    assert_eq!(Ok(elem), store.data_at(0));
+}
@@ -16,7 +16,11 @@
 
 //! Utiility functions unit tests
 
-use super::{family, family_path, is_leaf, is_left, node_height, peak_height_map, peaks};
+use super::{
+    ancestors, append_hash_ops, check_pos, check_size, expected_peak_count, family, family_path,
+    height_at_pos, inner_positions, is_leaf, is_left, leaf_count, mmr_size, node_height,
+    nodes_between, peak_height_map, peaks, proof_len, proof_positions,
+};
 
 #[test]
 fn peaks_works() {
@@ -48,6 +52,82 @@ fn peaks_works() {
     assert_eq!(peaks(1_048_555), want);
 }
 
+#[test]
+fn check_pos_works() {
+    use crate::Error;
+
+    assert_eq!(check_pos(1, 1), Ok(()));
+    assert_eq!(check_pos(11, 19), Ok(()));
+    assert_eq!(check_pos(19, 19), Ok(()));
+
+    assert_eq!(check_pos(0, 19), Err(Error::ZeroPosition));
+    assert_eq!(check_pos(0, 0), Err(Error::ZeroPosition));
+    assert_eq!(check_pos(20, 19), Err(Error::PositionOutOfRange(20, 19)));
+    assert_eq!(check_pos(1, 0), Err(Error::PositionOutOfRange(1, 0)));
+}
+
+#[test]
+fn check_size_works() {
+    use crate::Error;
+
+    for size in [0, 1, 3, 4, 7, 19] {
+        assert_eq!(check_size(size), Ok(()));
+    }
+
+    for size in [2, 5, 6, 9] {
+        assert_eq!(check_size(size), Err(Error::UnstableSize(size)));
+    }
+}
+
+#[test]
+fn leaf_count_works() {
+    assert_eq!(leaf_count(0), Some(0));
+    assert_eq!(leaf_count(1), Some(1));
+    assert_eq!(leaf_count(2), None);
+    assert_eq!(leaf_count(3), Some(2));
+    assert_eq!(leaf_count(4), Some(3));
+    assert_eq!(leaf_count(5), None);
+    assert_eq!(leaf_count(7), Some(4));
+    assert_eq!(leaf_count(10), Some(6));
+    assert_eq!(leaf_count(11), Some(7));
+    assert_eq!(leaf_count(19), Some(11));
+}
+
+#[test]
+fn mmr_size_works() {
+    assert_eq!(mmr_size(0), 0);
+    assert_eq!(mmr_size(1), 1);
+    assert_eq!(mmr_size(2), 3);
+    assert_eq!(mmr_size(3), 4);
+    assert_eq!(mmr_size(4), 7);
+    assert_eq!(mmr_size(6), 10);
+    assert_eq!(mmr_size(7), 11);
+    assert_eq!(mmr_size(11), 19);
+
+    for size in [1, 3, 4, 7, 10, 11, 19, 1_048_555] {
+        assert_eq!(mmr_size(leaf_count(size).unwrap()), size);
+    }
+
+    assert_eq!(mmr_size(u64::MAX / 2), u64::MAX - 64);
+    assert_eq!(mmr_size(u64::MAX / 2 + 1), u64::MAX);
+    assert_eq!(mmr_size(u64::MAX), u64::MAX);
+}
+
+#[test]
+fn expected_peak_count_works() {
+    assert_eq!(expected_peak_count(0), 0);
+    assert_eq!(expected_peak_count(1), 1);
+    assert_eq!(expected_peak_count(2), 0);
+    assert_eq!(expected_peak_count(3), 1);
+    assert_eq!(expected_peak_count(10), 2);
+    assert_eq!(expected_peak_count(11), 3);
+    assert_eq!(expected_peak_count(19), 3);
+
+    for size in [1, 3, 10, 11, 19, 1_048_555] {
+        assert_eq!(expected_peak_count(size), peaks(size).len());
+    }
+}
+
 #[test]
 fn node_height_works() {
     assert_eq!(node_height(0), 0);
@@ -70,6 +150,50 @@ fn node_height_works() {
     assert_eq!(node_height(31), 0);
 }
 
+#[test]
+fn height_at_pos_works() {
+    let want = [0, 0, 1, 0, 0, 1, 2, 0, 0, 1, 0, 0, 1, 2, 3, 0];
+
+    for (pos, &height) in (1..=16).zip(want.iter()) {
+        assert_eq!(height, height_at_pos(pos));
+        assert_eq!(node_height(pos - 1), height_at_pos(pos));
+    }
+
+    assert_eq!(0, height_at_pos(0));
+}
+
+#[test]
+fn inner_positions_works() {
+    assert_eq!(inner_positions(0).count(), 0);
+    assert_eq!(inner_positions(1).count(), 0);
+    assert_eq!(inner_positions(4).collect::<Vec<_>>(), [3]);
+    assert_eq!(inner_positions(7).collect::<Vec<_>>(), [3, 6, 7]);
+    assert_eq!(
+        inner_positions(19).collect::<Vec<_>>(),
+        [3, 6, 7, 10, 13, 14, 15, 18]
+    );
+}
+
+#[test]
+fn nodes_between_works() {
+    use crate::Error;
+
+    assert_eq!(nodes_between(1, 1, 1), Ok(1));
+    assert_eq!(nodes_between(1, 19, 19), Ok(19));
+    assert_eq!(nodes_between(4, 10, 19), Ok(7));
+    assert_eq!(nodes_between(10, 4, 19), Ok(0));
+
+    // the size of a MMR with 7 leaves
+    assert_eq!(nodes_between(1, 11, 19), Ok(mmr_size(7)));
+    assert_eq!(leaf_count(11), Some(7));
+
+    assert_eq!(nodes_between(0, 4, 19), Err(Error::ZeroPosition));
+    assert_eq!(
+        nodes_between(4, 20, 19),
+        Err(Error::PositionOutOfRange(20, 19))
+    );
+}
+
 #[test]
 fn is_leaf_works() {
     assert!(is_leaf(0));
@@ -112,6 +236,18 @@ fn peak_height_map_works() {
     assert_eq!(peak_height_map(u64::MAX - 1), (u64::MAX >> 1, 63));
 }
 
+#[test]
+fn append_hash_ops_works() {
+    assert_eq!(append_hash_ops(0), 2);
+    assert_eq!(append_hash_ops(1), 4);
+    assert_eq!(append_hash_ops(3), 2);
+    assert_eq!(append_hash_ops(4), 6);
+    assert_eq!(append_hash_ops(7), 2);
+    assert_eq!(append_hash_ops(10), 2);
+    assert_eq!(append_hash_ops(11), 8);
+    assert_eq!(append_hash_ops(19), 6);
+}
+
 #[test]
 fn is_left_works() {
     assert!(is_left(1));
@@ -182,3 +318,35 @@ fn family_path_invalid_args() {
     let path = family_path(12, 2);
     assert_eq!(EMPTY, path)
 }
+
+#[test]
+fn ancestors_works() {
+    assert_eq!(vec![10, 14, 15], ancestors(8, 15));
+    assert_eq!(vec![3, 7, 15], ancestors(1, 15));
+    assert_eq!(vec![10, 14], ancestors(8, 14));
+    assert_eq!(vec![15], ancestors(7, 15));
+    assert!(ancestors(15, 15).is_empty());
+    assert!(ancestors(11, 11).is_empty());
+}
+
+#[test]
+fn proof_positions_works() {
+    assert_eq!(proof_positions(1, 1), Vec::<u64>::new());
+    assert_eq!(proof_positions(1, 3), [2]);
+    assert_eq!(proof_positions(4, 4), [3]);
+    assert_eq!(proof_positions(1, 4), [2, 4]);
+    assert_eq!(proof_positions(5, 19), [4, 3, 14, 18, 19]);
+    assert_eq!(proof_positions(16, 19), [17, 19, 15]);
+    assert_eq!(proof_positions(19, 19), [18, 15]);
+}
+
+#[test]
+fn proof_len_works() {
+    assert_eq!(0, proof_len(1, 1));
+    assert_eq!(1, proof_len(1, 3));
+    assert_eq!(1, proof_len(4, 4));
+    assert_eq!(2, proof_len(1, 4));
+    assert_eq!(4, proof_len(5, 19));
+    assert_eq!(3, proof_len(16, 19));
+    assert_eq!(2, proof_len(19, 19));
+}
@@ -16,7 +16,7 @@
 
 //! Utiility functions mainly for MMR navigation
 
-use crate::{vec, Vec};
+use crate::{vec, Error, Result, Vec};
 
 #[cfg(test)]
 #[path = "util_tests.rs"]
@@ -69,6 +69,84 @@ pub(crate) fn peaks(size: u64) -> Vec<u64> {
     peaks
 }
 
+/// Check that `pos` is a valid node position for a MMR with `size` nodes.
+///
+/// Positions are `1` based, hence `pos == 0` is rejected as well as any position
+/// beyond `size`.
+pub(crate) fn check_pos(pos: u64, size: u64) -> Result<()> {
+    if pos == 0 {
+        return Err(Error::ZeroPosition);
+    }
+
+    if pos > size {
+        return Err(Error::PositionOutOfRange(pos, size));
+    }
+
+    Ok(())
+}
+
+/// Check that `size` is the size of a stable MMR.
+///
+/// See [`peaks`] for what makes a MMR unstable. An empty MMR is considered stable.
+pub(crate) fn check_size(size: u64) -> Result<()> {
+    if leaf_count(size).is_none() {
+        return Err(Error::UnstableSize(size));
+    }
+
+    Ok(())
+}
+
+/// Return the number of leaves for a MMR with `size` nodes.
+///
+/// `None` is returned for an unstable MMR, see [`peaks`] for what makes a MMR unstable.
+pub(crate) fn leaf_count(size: u64) -> Option<u64> {
+    if size == 0 {
+        return Some(0);
+    }
+
+    let mut peak_idx = ALL_ONES >> size.leading_zeros();
+    let mut nodes_left = size;
+    let mut leaves = 0;
+
+    while peak_idx != 0 {
+        if nodes_left >= peak_idx {
+            // a peak with `peak_idx` nodes has `(peak_idx + 1) / 2` leaves
+            leaves += (peak_idx >> 1) + 1;
+            nodes_left -= peak_idx;
+        }
+        peak_idx >>= 1;
+    }
+
+    if nodes_left > 0 {
+        return None;
+    }
+
+    Some(leaves)
+}
+
+/// Return the size, i.e. the total number of nodes, for a MMR with `leaf_count` leaves.
+///
+/// Every leaf but the first one of each peak is paired up with a parent node. Hence,
+/// the number of parent nodes is the leaf count minus the number of peaks.
+///
+/// The size is clamped to `u64::MAX` for leaf counts beyond `u64::MAX / 2`.
+pub(crate) fn mmr_size(leaf_count: u64) -> u64 {
+    leaf_count
+        .checked_mul(2)
+        .map_or(u64::MAX, |n| n - leaf_count.count_ones() as u64)
+}
+
+/// Return the number of peaks expected for a MMR with `size` nodes.
+///
+/// Every peak is a perfect binary tree with a power of two leaves and no two peaks
+/// have the same height. Hence, the number of peaks equals the number of set bits
+/// in the leaf count.
+///
+/// Like [`peaks`], `0` is returned for an unstable MMR.
+pub(crate) fn expected_peak_count(size: u64) -> usize {
+    leaf_count(size).map_or(0, |n| n.count_ones() as usize)
+}
+
 /// Return the height of a node at index `idx`.
 ///
 /// The height is calculated as if the node is part of a fully balanced binary
@@ -92,6 +170,36 @@ pub(crate) fn node_height(idx: u64) -> u64 {
     idx
 }
 
+/// Return the height of a node at position `pos`.
+///
+/// This is [`node_height`] for the index `pos - 1`. Positions are `1` based, hence
+/// `pos == 0` is not a valid position and treated as `pos == 1`, i.e. height `0`.
+pub(crate) fn height_at_pos(pos: u64) -> u64 {
+    node_height(pos.saturating_sub(1))
+}
+
+/// Return the positions of all inner, i.e. parent nodes for a MMR with `size` nodes.
+///
+/// Positions are yielded in ascending order.
+pub(crate) fn inner_positions(size: u64) -> impl Iterator<Item = u64> {
+    (1..=size).filter(|&pos| height_at_pos(pos) > 0)
+}
+
+/// Return the number of node positions in the inclusive range `start_pos..=end_pos`
+/// for a MMR with `size` nodes.
+///
+/// Every MMR position is a node, either a leaf or a parent. Hence, this is just the
+/// difference of both positions plus one, or `0` if `start_pos > end_pos`. Note that
+/// this is not the number of leaves in the range. For example, `nodes_between(1, s, s)`
+/// is the size `s` of a MMR with `leaf_count(s)` leaves, see [`leaf_count`] and
+/// [`mmr_size`].
+pub(crate) fn nodes_between(start_pos: u64, end_pos: u64, size: u64) -> Result<u64> {
+    check_pos(start_pos, size)?;
+    check_pos(end_pos, size)?;
+
+    Ok((end_pos + 1).saturating_sub(start_pos))
+}
+
 /// Return true if the node at `idx` is a leaf node.
 ///
 /// This is a convenience wrapper around [`node_height`]
@@ -134,6 +242,22 @@ pub(crate) fn peak_height_map(mut idx: u64) -> (u64, u64) {
     (peak_map, idx)
 }
 
+/// Return the number of Blake2b hash invocations the next append to a MMR with `size`
+/// nodes will perform.
+///
+/// Appending a leaf hashes the encoded leaf and salts that hash with the leaf index.
+/// Every parent created while merging peaks of equal height hashes its child hashes
+/// and salts that hash with the parent index. Hence, each new node accounts for two
+/// hash invocations. Checkpoint roots and paranoid validation are not accounted for.
+pub(crate) fn append_hash_ops(size: u64) -> u64 {
+    let (peak_map, _) = peak_height_map(size);
+
+    // every trailing peak is merged with the new node
+    let new = 1 + peak_map.trailing_ones() as u64;
+
+    2 * new
+}
+
 /// Is the node at `pos` the left child node of its parent.
 pub(crate) fn is_left(pos: u64) -> bool {
     let (peak_map, node_height) = peak_height_map(pos - 1);
@@ -212,3 +336,48 @@ pub(crate) fn family_path(pos: u64, end_pos: u64) -> Vec<(u64, u64)> {
 
     path
 }
+
+/// Return the positions of all ancestors of the node at position `pos` in a MMR with
+/// `size` nodes, i.e. the parent positions along the family path up to the peak.
+///
+/// For example, given the tree shown for [`family_path`], the ancestors of node '8'
+/// are `[10, 14, 15]`.
+pub(crate) fn ancestors(pos: u64, size: u64) -> Vec<u64> {
+    family_path(pos, size).into_iter().map(|n| n.0).collect()
+}
+
+/// Return the positions of all nodes needed to generate a membership proof for the
+/// leaf at position `pos` in a MMR with `size` nodes.
+///
+/// Positions are listed in proof path order. First the siblings along the family
+/// path up to the peak the leaf belongs to, then the peaks lower than that peak from
+/// left to right, and finally the higher peaks from right to left.
+///
+/// Note that within the proof, the lower peaks are bagged into a single hash.
+pub(crate) fn proof_positions(pos: u64, size: u64) -> Vec<u64> {
+    let family = family_path(pos, size);
+    let peak = family.last().map_or(pos, |n| n.0);
+    let peaks = peaks(size);
+
+    let mut positions = family.iter().map(|n| n.1).collect::<Vec<_>>();
+
+    positions.extend(peaks.iter().filter(|&&p| p > peak));
+    positions.extend(peaks.iter().rev().filter(|&&p| p < peak));
+
+    positions
+}
+
+/// Return the proof path length for the leaf at position `pos` in a MMR with `size` nodes.
+///
+/// This is the number of [`proof_positions`], except that all lower peaks account for
+/// a single, bagged hash.
+pub(crate) fn proof_len(pos: u64, size: u64) -> usize {
+    let family = family_path(pos, size);
+    let peak = family.last().map_or(pos, |n| n.0);
+    let peaks = peaks(size);
+
+    let lower = peaks.iter().filter(|&&p| p > peak).count();
+    let higher = peaks.iter().filter(|&&p| p < peak).count();
+
+    family.len() + lower.min(1) + higher
+}
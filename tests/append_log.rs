@@ -0,0 +1,69 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! MMR append-only log tests
+
+use arber::{AppendLog, Error, Result};
+
+type E = Vec<u8>;
+
+#[test]
+fn append_log_works() -> Result<()> {
+    let mut log = AppendLog::<E>::new();
+
+    assert!(log.is_empty());
+
+    for i in 0..10u8 {
+        assert_eq!(i as u64, log.append(&vec![i])?);
+    }
+
+    assert_eq!(10, log.len());
+
+    for i in 0..10u8 {
+        assert_eq!(Some(vec![i]), log.get(i as u64));
+    }
+
+    assert_eq!(None, log.get(10));
+
+    let root = log.root()?;
+    let proof = log.proof(3)?;
+
+    assert_eq!(5, AppendLog::<E>::position(3));
+    assert!(proof.verify(root, &vec![3u8], AppendLog::<E>::position(3))?);
+
+    Ok(())
+}
+
+#[test]
+fn append_log_proof_fails() -> Result<()> {
+    let mut log = AppendLog::<E>::new();
+
+    assert_eq!(Err(Error::LeafIndexOutOfRange(0, 0)), log.proof(0));
+
+    for i in 0..10u8 {
+        log.append(&vec![i])?;
+    }
+
+    assert_eq!(Err(Error::LeafIndexOutOfRange(10, 10)), log.proof(10));
+
+    let proof = log.proof(3)?;
+
+    assert!(proof
+        .verify(log.root()?, &vec![4u8], AppendLog::<E>::position(3))
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Merkle batch proof tests
+
+use arber::{Error, IndexEncoding, MerkleMountainRange, Result, VecStore};
+
+type E = Vec<u8>;
+
+fn make_mmr(num_leafs: u8) -> MerkleMountainRange<E, VecStore<E>> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    (0..=num_leafs.saturating_sub(1)).for_each(|i| {
+        let n = vec![i];
+        let _ = mmr.append(&n).unwrap();
+    });
+
+    mmr
+}
+
+#[test]
+fn batch_proof_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    // leaves below a single peak, sharing most of their path
+    let proof = mmr.batch_proof(&[1, 2, 4])?;
+
+    assert_eq!(19, proof.mmr_size);
+    assert_eq!(4, proof.path.len());
+    assert!(proof.verify(root, &[(1, vec![0u8]), (2, vec![1u8]), (4, vec![2u8])])?);
+
+    // leaves below all three peaks
+    let proof = mmr.batch_proof(&[5, 16, 19])?;
+
+    assert!(proof.verify(root, &[(5, vec![3u8]), (16, vec![8u8]), (19, vec![10u8])])?);
+
+    // all leaves, nothing but the leaves is needed
+    let positions = [1, 2, 4, 5, 8, 9, 11, 12, 16, 17, 19];
+    let proof = mmr.batch_proof(&positions)?;
+    let leaves = positions
+        .iter()
+        .enumerate()
+        .map(|(i, &pos)| (pos, vec![i as u8]))
+        .collect::<Vec<_>>();
+
+    assert!(proof.path.is_empty());
+    assert!(proof.verify(root, &leaves)?);
+
+    Ok(())
+}
+
+#[test]
+fn batch_proof_fails() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+    let proof = mmr.batch_proof(&[5, 16])?;
+
+    assert!(matches!(
+        proof.verify(root, &[(5, vec![3u8]), (16, vec![9u8])]),
+        Err(Error::InvalidRootHash(_, _))
+    ));
+
+    // proof path is too short
+    let proof_12 = mmr.batch_proof(&[1, 2])?;
+
+    assert_eq!(
+        Err(Error::InvalidProofLength),
+        proof_12.verify(root, &[(1, vec![0u8])])
+    );
+
+    // proof path is too long
+    let proof_1 = mmr.batch_proof(&[1])?;
+
+    assert_eq!(
+        Err(Error::InvalidProofLength),
+        proof_1.verify(root, &[(1, vec![0u8]), (2, vec![1u8])])
+    );
+
+    assert_eq!(
+        Err(Error::UnsortedPositions),
+        proof.verify(root, &[(16, vec![8u8]), (5, vec![3u8])])
+    );
+
+    assert_eq!(
+        Error::UnsortedPositions,
+        mmr.batch_proof(&[5, 5]).err().unwrap()
+    );
+    assert_eq!(
+        Error::ExpectingLeafNode(3),
+        mmr.batch_proof(&[3]).err().unwrap()
+    );
+    assert_eq!(
+        Error::PositionOutOfRange(20, 19),
+        mmr.batch_proof(&[20]).err().unwrap()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn append_batch_proof_works() -> Result<()> {
+    let mut mmr = make_mmr(8);
+    let leaves = vec![vec![8u8], vec![9u8], vec![10u8]];
+
+    let (size, proof) = mmr.append_batch_proof(&leaves)?;
+
+    assert_eq!(19, size);
+    assert_eq!(size, proof.mmr_size);
+    assert_eq!(make_mmr(11).root()?, mmr.root()?);
+
+    let leaves = vec![16u64, 17, 19]
+        .into_iter()
+        .zip(leaves)
+        .collect::<Vec<_>>();
+
+    assert!(proof.verify(mmr.root()?, &leaves)?);
+
+    Ok(())
+}
+
+#[test]
+fn merge_proofs_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    let proof = mmr.proof(5)?.merge(mmr.proof(17)?, 5, 17)?;
+
+    assert_eq!(mmr.batch_proof(&[5, 17])?, proof);
+    assert!(proof.verify(root, &[(5, vec![3u8]), (17, vec![9u8])])?);
+
+    // proofs can be merged in any order
+    let proof = mmr.proof(19)?.merge(mmr.proof(1)?, 19, 1)?;
+
+    assert!(proof.verify(root, &[(1, vec![0u8]), (19, vec![10u8])])?);
+
+    // sibling leaves, only the peaks 15 and 19 are needed
+    let proof = mmr.proof(16)?.merge(mmr.proof(17)?, 16, 17)?;
+
+    assert_eq!(vec![mmr.hash(15)?, mmr.hash(19)?], proof.path);
+    assert!(proof.verify(root, &[(16, vec![8u8]), (17, vec![9u8])])?);
+
+    Ok(())
+}
+
+#[test]
+fn merge_proofs_fails() -> Result<()> {
+    let mmr = make_mmr(11);
+
+    let got = mmr
+        .proof(5)?
+        .merge(make_mmr(7).proof(5)?, 5, 5)
+        .err()
+        .unwrap();
+
+    assert_eq!(Error::MmrSizeMismatch(19, 11), got);
+
+    // peaks 18 and 19 are bagged into a single hash in both proofs
+    let got = mmr.proof(1)?.merge(mmr.proof(5)?, 1, 5).err().unwrap();
+
+    assert_eq!(Error::MissingProofNode(18), got);
+
+    Ok(())
+}
+
+#[test]
+fn prove_many_leaf_indices_works() -> Result<()> {
+    let mmr = make_mmr(8);
+    let root = mmr.root()?;
+
+    let proof = mmr.prove_many_leaf_indices(&[0, 2, 5])?;
+
+    assert_eq!(mmr.batch_proof(&[1, 4, 9])?, proof);
+    assert!(proof.verify(root, &[(1, vec![0u8]), (4, vec![2u8]), (9, vec![5u8])])?);
+
+    assert_eq!(
+        Error::LeafIndexOutOfRange(8, 8),
+        mmr.prove_many_leaf_indices(&[0, 8]).err().unwrap()
+    );
+    assert_eq!(
+        Error::UnsortedPositions,
+        mmr.prove_many_leaf_indices(&[2, 0]).err().unwrap()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn batch_proof_index_encoding_works() -> Result<()> {
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, VecStore::<E>::new())
+        .with_index_encoding(IndexEncoding::BigEndian);
+
+    (0..11u8).try_for_each(|i| mmr.append(&vec![i]).map(|_| ()))?;
+
+    let root = mmr.root()?;
+    let leaves = [(1, vec![0u8]), (5, vec![3u8]), (16, vec![8u8])];
+    let proof = mmr.batch_proof(&[1, 5, 16])?;
+
+    assert!(proof.verify_with_index_encoding(root, &leaves, IndexEncoding::BigEndian)?);
+
+    let got = proof.verify(root, &leaves).err().unwrap();
+    assert!(matches!(got, Error::InvalidRootHash(_, _)));
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! MMR counting store tests
+
+#![cfg(feature = "test-util")]
+
+use arber::{CountingStore, MerkleMountainRange, Result, Store, VecStore};
+
+type E = Vec<u8>;
+
+fn make_mmr(num_leafs: u8) -> MerkleMountainRange<E, CountingStore<VecStore<E>>> {
+    let s = CountingStore::new(VecStore::<E>::new());
+    let mut mmr = MerkleMountainRange::<E, CountingStore<VecStore<E>>>::new(0, s);
+
+    (0..=num_leafs.saturating_sub(1)).for_each(|i| {
+        let n = vec![i];
+        let _ = mmr.append(&n).unwrap();
+    });
+
+    mmr
+}
+
+#[test]
+fn counting_store_counts_writes() {
+    let mmr = make_mmr(11);
+
+    assert_eq!(11, mmr.store().writes());
+    assert_eq!(19, Store::<E>::len(mmr.store()));
+}
+
+#[test]
+fn counting_store_counts_proof_reads() -> Result<()> {
+    let mmr = make_mmr(11);
+    mmr.store().reset();
+
+    // the leaf itself, 3 siblings, 1 lower peak and 1 higher peak
+    let proof = mmr.proof(5)?;
+
+    assert_eq!(6, mmr.store().reads());
+    assert_eq!(0, mmr.store().writes());
+    assert_eq!(4, proof.path.len());
+
+    Ok(())
+}
@@ -16,8 +16,12 @@
 
 //! Merkle proof store tests
 
-use arber::{hash_with_index, Hashable, MerkleMountainRange, MerkleProof, Result, VecStore};
+use arber::{
+    hash_with_index, Error, FullProof, Hash, Hashable, IndexEncoding, MerkleMountainRange,
+    MerkleProof, ProofSummary, ProofVerifier, Result, VecStore, VerifiedProofCache,
+};
 use codec::{DecodeAll, Encode};
+use scale_info::{TypeDef, TypeInfo};
 
 type E = Vec<u8>;
 
@@ -120,3 +124,840 @@ fn proof_encode_decode() {
 
     assert!(proof.verify(mmr.root().unwrap(), &vec![3u8], 5).unwrap());
 }
+
+#[test]
+fn verify_strict_works() -> Result<()> {
+    let mmr = make_mmr(4);
+    let proof = mmr.proof(5)?;
+    let root = mmr.root()?;
+
+    assert!(proof.verify_strict(root, &vec![3u8], 5, None)?);
+    assert!(proof.verify_strict(root, &vec![3u8], 5, Some(mmr.hash(5)?))?);
+
+    Ok(())
+}
+
+#[test]
+fn verify_strict_fails() -> Result<()> {
+    let mmr = make_mmr(4);
+    let proof = mmr.proof(5)?;
+    let root = mmr.root()?;
+    let leaf_hash = mmr.hash(5)?;
+
+    let elem = vec![4u8];
+    let want = Error::LeafHashMismatch(hash_with_index(4, &elem.encode().hash()), leaf_hash);
+    let got = proof
+        .verify_strict(root, &elem, 5, Some(leaf_hash))
+        .err()
+        .unwrap();
+
+    assert_eq!(want, got);
+
+    // without an expected leaf hash, verification fails at the root
+    let got = proof.verify_strict(root, &elem, 5, None).err().unwrap();
+
+    assert!(matches!(got, Error::InvalidRootHash(_, _)));
+
+    Ok(())
+}
+
+#[test]
+fn verify_typed_works() -> Result<()> {
+    let s = VecStore::<u32>::new();
+    let mut mmr = MerkleMountainRange::<u32, VecStore<u32>>::new(0, s);
+
+    for i in 0..7u32 {
+        mmr.append(&(i * 100))?;
+    }
+
+    let root = mmr.root()?;
+    let proof = mmr.proof(8)?;
+
+    assert!(proof.verify(root, &400u32, 8)?);
+    assert!(proof.verify_typed(root, &400u32, 8)?);
+
+    let want = proof.verify(root, &500u32, 8).err().unwrap();
+    let got = proof.verify_typed(root, &500u32, 8).err().unwrap();
+
+    assert_eq!(want, got);
+
+    Ok(())
+}
+
+#[test]
+fn verify_with_index_encoding_works() -> Result<()> {
+    let mut le = make_mmr(0);
+    let mut be = MerkleMountainRange::<E, VecStore<E>>::new(0, VecStore::<E>::new())
+        .with_index_encoding(IndexEncoding::BigEndian);
+
+    be.append(&vec![0u8])?;
+
+    for i in 1..11u8 {
+        le.append(&vec![i])?;
+        be.append(&vec![i])?;
+    }
+
+    assert!(le.validate()?);
+    assert!(be.validate()?);
+
+    // index 0 is the same in either byte order
+    assert_eq!(le.hash(1)?, be.hash(1)?);
+    assert_ne!(le.hash(2)?, be.hash(2)?);
+    assert_ne!(le.root()?, be.root()?);
+
+    for &(pos, elem) in [(1u64, 0u8), (5, 3), (11, 6), (19, 10)].iter() {
+        let proof = le.proof(pos)?;
+        assert!(proof.verify(le.root()?, &vec![elem], pos)?);
+
+        let proof = be.proof(pos)?;
+        assert!(proof.verify_with_index_encoding(
+            be.root()?,
+            &vec![elem],
+            pos,
+            IndexEncoding::BigEndian
+        )?);
+
+        let got = proof.verify(be.root()?, &vec![elem], pos).err().unwrap();
+        assert!(matches!(got, Error::InvalidRootHash(_, _)));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn verify_variants_index_encoding_works() -> Result<()> {
+    let be_enc = IndexEncoding::BigEndian;
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, VecStore::<E>::new())
+        .with_index_encoding(be_enc);
+
+    (0..11u8).try_for_each(|i| mmr.append(&vec![i]).map(|_| ()))?;
+
+    let root = mmr.root()?;
+    let elem = vec![3u8];
+    let proof = mmr.proof(5)?;
+
+    assert_eq!(
+        Some(1),
+        proof.verify_any_with_index_encoding(&[Hash::default(), root], &elem, 5, be_enc)?
+    );
+    assert_eq!(None, proof.verify_any(&[Hash::default(), root], &elem, 5)?);
+
+    let leaf_hash = mmr.hash(5)?;
+    assert!(proof.verify_strict_with_index_encoding(root, &elem, 5, Some(leaf_hash), be_enc)?);
+    assert!(matches!(
+        proof.verify_strict(root, &elem, 5, Some(leaf_hash)),
+        Err(Error::LeafHashMismatch(_, _))
+    ));
+
+    let proof = mmr.proof_within_peak(5)?;
+    let peak_hash = mmr.hash(15)?;
+    assert!(proof.verify_within_peak_with_index_encoding(peak_hash, &elem, 5, be_enc)?);
+    assert!(proof.verify_within_peak(peak_hash, &elem, 5).is_err());
+
+    let mut mmr = MerkleMountainRange::<u32, VecStore<u32>>::new(0, VecStore::new())
+        .with_index_encoding(be_enc);
+
+    (0..7u32).try_for_each(|i| mmr.append(&(i * 100)).map(|_| ()))?;
+
+    let root = mmr.root()?;
+    let proof = mmr.proof(8)?;
+
+    assert!(proof.verify_typed_with_index_encoding(root, &400u32, 8, be_enc)?);
+    assert!(proof.verify_typed(root, &400u32, 8).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn verify_with_peaks_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+    let proof = mmr.proof(16)?;
+
+    assert!(proof.verify_with_peaks(root, &vec![8u8], 16, &[15, 18, 19])?);
+
+    let got = proof
+        .verify_with_peaks(root, &vec![8u8], 16, &[19, 18, 15])
+        .err()
+        .unwrap();
+
+    assert_eq!(Error::UnsortedPeaks, got);
+
+    Ok(())
+}
+
+#[test]
+fn proof_positions_works() -> Result<()> {
+    let mmr = make_mmr(11);
+
+    let proof = mmr.proof(5)?;
+    let positions = proof.positions(5);
+
+    assert_eq!(vec![4, 3, 14, 18], positions);
+    assert_eq!(proof.path.len(), positions.len());
+
+    for (pos, hash) in positions.iter().zip(proof.path.iter()).take(3) {
+        assert_eq!(mmr.hash(*pos)?, *hash);
+    }
+
+    let proof = mmr.proof(17)?;
+
+    assert_eq!(vec![16, 19, 15], proof.positions(17));
+    assert_eq!(proof.path.len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn verify_prehashed_leaf_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    for (i, &pos) in [1u64, 2, 4, 5, 8, 9, 11, 12, 16, 17, 19].iter().enumerate() {
+        let elem = vec![i as u8];
+        let leaf_node_hash = hash_with_index(pos - 1, &elem.encode().hash());
+        let proof = mmr.proof(pos)?;
+
+        assert_eq!(
+            proof.verify(root, &elem, pos)?,
+            proof.verify_prehashed_leaf(root, leaf_node_hash, pos)?
+        );
+    }
+
+    let proof = mmr.proof(5)?;
+    let got = proof
+        .verify_prehashed_leaf(root, vec![3u8].encode().hash(), 5)
+        .err()
+        .unwrap();
+
+    assert!(matches!(got, Error::InvalidRootHash(_, _)));
+
+    Ok(())
+}
+
+#[test]
+fn append_root_of_works() -> Result<()> {
+    let sub1 = make_mmr(4);
+    let sub2 = make_mmr(7);
+
+    let s = VecStore::<Hash>::new();
+    let mut outer = MerkleMountainRange::<Hash, VecStore<Hash>>::new(0, s);
+
+    outer.append_root_of(&sub1)?;
+    outer.append_root_of(&sub2)?;
+
+    let root = outer.root()?;
+
+    let proof = outer.proof(2)?;
+    assert!(proof.verify(root, &sub2.root()?, 2)?);
+
+    let got = proof.verify(root, &sub1.root()?, 2).err().unwrap();
+    assert!(matches!(got, Error::InvalidRootHash(_, _)));
+
+    // inner leaf inclusion, chained with the inner root inclusion
+    let proof = sub1.proof(4)?;
+    assert!(proof.verify(sub1.root()?, &vec![2u8], 4)?);
+    assert!(outer.proof(1)?.verify(root, &sub1.root()?, 1)?);
+
+    Ok(())
+}
+
+#[test]
+fn proof_path_order_works() -> Result<()> {
+    let mmr = make_mmr(11);
+
+    for &(leaf, sibling) in [(1u64, 2u64), (2, 1), (5, 4), (9, 8), (12, 11), (17, 16)].iter() {
+        let proof = mmr.proof(leaf)?;
+
+        assert_eq!(mmr.hash(sibling)?, proof.path[0]);
+        assert_eq!(Some(&proof.path[0]), proof.from_leaf_to_root().next());
+        assert_eq!(Some(&proof.path[0]), proof.from_root_to_leaf().last());
+
+        let mut reversed = proof.from_root_to_leaf().cloned().collect::<Vec<_>>();
+        reversed.reverse();
+
+        assert_eq!(proof.path, reversed);
+    }
+
+    // a lone leaf being the last peak has the higher peaks as path only
+    let proof = mmr.proof(19)?;
+
+    assert_eq!(mmr.hash(18)?, proof.path[0]);
+    assert_eq!(Some(&mmr.hash(15)?), proof.from_root_to_leaf().next());
+
+    Ok(())
+}
+
+#[test]
+fn verify_proof_middle_peak() -> Result<()> {
+    // size 19 MMR with peaks at 15, 18 and 19
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    // leaves below the middle peak 18, folded with the bagged lower peak 19 and
+    // the higher peak 15
+    let proof = mmr.proof(16)?;
+    assert_eq!(vec![17, 19, 15], proof.positions(16));
+    assert!(proof.verify(root, &vec![8u8], 16)?);
+
+    let proof = mmr.proof(17)?;
+    assert!(proof.verify(root, &vec![9u8], 17)?);
+
+    // a leaf can only be a peak itself, if it is the last peak
+    let proof = mmr.proof(19)?;
+    assert_eq!(vec![18, 15], proof.positions(19));
+    assert!(proof.verify(root, &vec![10u8], 19)?);
+
+    let got = proof.verify(root, &vec![9u8], 19).err().unwrap();
+    assert!(matches!(got, Error::InvalidRootHash(_, _)));
+
+    Ok(())
+}
+
+#[test]
+fn append_hash_works() -> Result<()> {
+    let leaves = (0..11u32).map(|i| i.hash()).collect::<Vec<_>>();
+
+    let s = VecStore::<Hash>::new();
+    let mut mmr = MerkleMountainRange::<Hash, VecStore<Hash>>::new(0, s);
+
+    let s = VecStore::<Hash>::new();
+    let mut encoded = MerkleMountainRange::<Hash, VecStore<Hash>>::new(0, s);
+
+    for leaf in &leaves {
+        mmr.append_hash(leaf)?;
+        encoded.append(leaf)?;
+    }
+
+    // the leaf hash is used as is
+    assert_eq!(hash_with_index(0, &leaves[0]), mmr.hash(1)?);
+    assert_eq!(hash_with_index(3, &leaves[2]), mmr.hash(4)?);
+    assert_eq!(
+        hash_with_index(0, &leaves[0].encode().hash()),
+        encoded.hash(1)?
+    );
+    assert_ne!(mmr.root()?, encoded.root()?);
+    assert!(mmr.validate()?);
+
+    let root = mmr.root()?;
+    let proof = mmr.proof(5)?;
+
+    assert!(proof.verify_typed(root, &leaves[3], 5)?);
+    assert!(proof.verify_prehashed_leaf(root, hash_with_index(4, &leaves[3]), 5)?);
+
+    Ok(())
+}
+
+#[test]
+fn full_proof_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    let proof = FullProof::new(mmr.proof(5)?, 5, vec![3u8]);
+    let bytes = proof.encode();
+    let decoded = FullProof::<E>::decode_all(&mut &bytes[..]).unwrap();
+
+    assert_eq!(proof, decoded);
+    assert!(decoded.verify(root)?);
+
+    let mut altered = decoded;
+    altered.leaf = vec![4u8];
+
+    let got = altered.verify(root).err().unwrap();
+
+    assert!(matches!(got, Error::InvalidRootHash(_, _)));
+
+    Ok(())
+}
+
+#[test]
+fn proof_summary_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let proof = mmr.proof(5)?;
+
+    // 8 bytes size, 1 byte compact path length and 4 path hashes
+    let want = ProofSummary {
+        mmr_size: 19,
+        num_siblings: 4,
+        total_bytes: 8 + 1 + 4 * 32,
+    };
+
+    assert_eq!(want, proof.summary());
+    assert_eq!(proof.encode().len(), proof.summary().total_bytes);
+
+    Ok(())
+}
+
+#[test]
+fn verify_any_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let proof = mmr.proof(5)?;
+
+    let roots = [make_mmr(10).root()?, mmr.root()?, make_mmr(12).root()?];
+
+    assert_eq!(Some(1), proof.verify_any(&roots, &vec![3u8], 5)?);
+    assert_eq!(None, proof.verify_any(&roots, &vec![4u8], 5)?);
+    assert_eq!(None, proof.verify_any(&[], &vec![3u8], 5)?);
+
+    Ok(())
+}
+
+#[test]
+fn proof_encode_without_size() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+    let leafs = [(1u64, 0u8), (5, 3), (16, 8), (19, 10)];
+
+    // a batch of proofs sharing the same size, which is encoded only once
+    let batch = (
+        mmr.size(),
+        leafs
+            .iter()
+            .map(|(pos, _)| mmr.proof(*pos).unwrap().encode_without_size())
+            .collect::<Vec<_>>(),
+    )
+        .encode();
+
+    let (mmr_size, paths) = <(u64, Vec<Vec<u8>>)>::decode_all(&mut batch.as_slice()).unwrap();
+
+    for ((pos, elem), path) in leafs.iter().zip(paths) {
+        let proof = MerkleProof::decode_with_size(&path, mmr_size)?;
+
+        assert_eq!(mmr.proof(*pos)?, proof);
+        assert!(proof.verify(root, &vec![*elem], *pos)?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn proof_decode_with_size_fails() -> Result<()> {
+    let mmr = make_mmr(11);
+    let mut bytes = mmr.proof(5)?.encode_without_size();
+    bytes.pop();
+
+    assert_eq!(
+        Error::InvalidProofEncoding,
+        MerkleProof::decode_with_size(&bytes, mmr.size())
+            .err()
+            .unwrap()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn proof_trace_works() -> Result<()> {
+    let mmr = make_mmr(11);
+
+    let proof = mmr.proof(5)?;
+    let want = vec![(5, false), (6, false), (7, true), (15, true)];
+
+    assert_eq!(want, proof.trace(5));
+
+    let proof = mmr.proof(19)?;
+    let want = vec![(19, false), (21, false)];
+
+    assert_eq!(want, proof.trace(19));
+
+    let proof = mmr.proof(1)?;
+    let want = vec![(1, true), (3, true), (7, true), (15, true)];
+
+    assert_eq!(want, proof.trace(1));
+
+    Ok(())
+}
+
+#[test]
+fn verify_cached_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+    let proof = mmr.proof(5)?;
+    let mut cache = VerifiedProofCache::new(2);
+
+    assert!(proof.verify_cached(root, &vec![3u8], 5, &mut cache)?);
+    assert_eq!(0, cache.hits());
+    assert_eq!(1, cache.len());
+
+    assert!(proof.verify_cached(root, &vec![3u8], 5, &mut cache)?);
+    assert_eq!(1, cache.hits());
+
+    // failed verifications are not cached
+    assert!(proof
+        .verify_cached(root, &vec![4u8], 5, &mut cache)
+        .is_err());
+    assert!(proof
+        .verify_cached(root, &vec![4u8], 5, &mut cache)
+        .is_err());
+    assert_eq!(1, cache.hits());
+    assert_eq!(1, cache.len());
+
+    // the oldest entry is evicted once the cache is full
+    let p16 = mmr.proof(16)?;
+    let p19 = mmr.proof(19)?;
+
+    assert!(p16.verify_cached(root, &vec![8u8], 16, &mut cache)?);
+    assert!(p19.verify_cached(root, &vec![10u8], 19, &mut cache)?);
+    assert_eq!(2, cache.len());
+
+    assert!(proof.verify_cached(root, &vec![3u8], 5, &mut cache)?);
+    assert_eq!(1, cache.hits());
+
+    Ok(())
+}
+
+#[test]
+fn proof_flat_bytes_works() -> Result<()> {
+    let mmr = make_mmr(11);
+
+    for pos in [1, 5, 16, 19] {
+        let proof = mmr.proof(pos)?;
+        let bytes = proof.to_flat_bytes();
+
+        assert_eq!(8 + 32 * proof.path.len(), bytes.len());
+        assert_eq!(19u64.to_be_bytes(), bytes[..8]);
+        assert_eq!(proof, MerkleProof::from_flat_bytes(&bytes)?);
+    }
+
+    let bytes = mmr.proof(5)?.to_flat_bytes();
+
+    assert_eq!(
+        Error::InvalidProofEncoding,
+        MerkleProof::from_flat_bytes(&bytes[..bytes.len() - 1])
+            .err()
+            .unwrap()
+    );
+    assert_eq!(
+        Error::InvalidProofEncoding,
+        MerkleProof::from_flat_bytes(&bytes[..7]).err().unwrap()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn proof_type_info() {
+    let ty = MerkleProof::type_info();
+
+    assert_eq!("MerkleProof", ty.path.ident().unwrap());
+
+    match ty.type_def {
+        TypeDef::Composite(c) => {
+            let fields = c.fields.iter().map(|f| f.name.unwrap()).collect::<Vec<_>>();
+
+            assert_eq!(vec!["mmr_size", "path"], fields);
+        }
+        _ => panic!("expecting a composite type"),
+    }
+}
+
+#[test]
+fn proof_verifier_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    for (pos, elem) in [(1u64, 0u8), (5, 3), (12, 7), (16, 8), (19, 10)] {
+        let proof = mmr.proof(pos)?;
+        let mut verifier = ProofVerifier::new(root, &vec![elem], pos, proof.mmr_size);
+
+        for h in proof.path.iter() {
+            verifier.feed(*h)?;
+        }
+
+        assert_eq!(proof.verify(root, &vec![elem], pos)?, verifier.finish()?);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn proof_verifier_fails() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+    let proof = mmr.proof(5)?;
+
+    // wrong element
+    let mut verifier = ProofVerifier::new(root, &vec![4u8], 5, proof.mmr_size);
+    proof.path.iter().try_for_each(|h| verifier.feed(*h))?;
+
+    assert!(matches!(
+        verifier.finish(),
+        Err(Error::InvalidRootHash(_, _))
+    ));
+
+    // truncated path
+    let mut verifier = ProofVerifier::new(root, &vec![3u8], 5, proof.mmr_size);
+    proof.path[..3].iter().try_for_each(|h| verifier.feed(*h))?;
+
+    assert!(matches!(
+        verifier.finish(),
+        Err(Error::InvalidRootHash(_, _))
+    ));
+
+    // path longer than any valid proof
+    let max = MerkleProof::max_expected_len(proof.mmr_size);
+    let mut verifier = ProofVerifier::new(root, &vec![3u8], 5, proof.mmr_size);
+    (0..max).try_for_each(|_| verifier.feed(Hash::default()))?;
+
+    assert_eq!(
+        verifier.feed(Hash::default()),
+        Err(Error::ProofTooLong(max as u64 + 1, max as u64))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn proof_verifier_index_encoding_works() -> Result<()> {
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, VecStore::<E>::new())
+        .with_index_encoding(IndexEncoding::BigEndian);
+
+    (0..11u8).try_for_each(|i| mmr.append(&vec![i]).map(|_| ()))?;
+
+    let root = mmr.root()?;
+    let proof = mmr.proof(5)?;
+
+    let mut be = ProofVerifier::with_index_encoding(
+        root,
+        &vec![3u8],
+        5,
+        proof.mmr_size,
+        IndexEncoding::BigEndian,
+    );
+    let mut le = ProofVerifier::new(root, &vec![3u8], 5, proof.mmr_size);
+
+    for h in proof.path.iter() {
+        be.feed(*h)?;
+        le.feed(*h)?;
+    }
+
+    assert!(be.finish()?);
+    assert!(matches!(le.finish(), Err(Error::InvalidRootHash(_, _))));
+
+    Ok(())
+}
+
+#[test]
+fn reconstruct_peaks_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let peaks = mmr.peaks()?;
+
+    // below the middle peak
+    let proof = mmr.proof(17)?;
+    assert_eq!(peaks, proof.reconstruct_peaks(&vec![9u8], 17)?);
+
+    // below the rightmost peak
+    let proof = mmr.proof(19)?;
+    assert_eq!(peaks, proof.reconstruct_peaks(&vec![10u8], 19)?);
+
+    // below the leftmost peak, lower peaks are bagged together
+    let proof = mmr.proof(5)?;
+    let bagged = hash_with_index(mmr.size(), &(peaks[1], peaks[2]).hash());
+
+    assert_eq!(
+        vec![peaks[0], bagged],
+        proof.reconstruct_peaks(&vec![3u8], 5)?
+    );
+
+    // the proof path does not fit the position
+    assert_eq!(
+        Err(Error::InvalidProofLength),
+        proof.reconstruct_peaks(&vec![10u8], 19)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn max_expected_len_works() {
+    assert_eq!(0, MerkleProof::max_expected_len(0));
+    assert_eq!(1, MerkleProof::max_expected_len(1));
+    assert_eq!(2, MerkleProof::max_expected_len(3));
+    // 11 leaves, peaks at heights 3, 1 and 0
+    assert_eq!(6, MerkleProof::max_expected_len(19));
+}
+
+#[test]
+fn verify_bounded_works() -> Result<()> {
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    for (i, pos) in [1u64, 5, 17, 19].iter().enumerate() {
+        let proof = mmr.proof(*pos)?;
+        let leaf = [0u8, 3, 9, 10][i];
+
+        assert!(proof.path.len() <= MerkleProof::max_expected_len(mmr.size()));
+        assert!(proof.verify_bounded(root, &vec![leaf], *pos)?);
+    }
+
+    let mut proof = mmr.proof(5)?;
+    proof.path.extend(vec![Hash::default(); 3]);
+
+    assert_eq!(
+        Err(Error::ProofTooLong(7, 6)),
+        proof.verify_bounded(root, &vec![3u8], 5)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn verify_within_peak_works() -> Result<()> {
+    let mmr = make_mmr(7);
+    let peaks = mmr.peaks()?;
+    let proof = mmr.proof_within_peak(1)?;
+
+    assert_eq!(2, proof.path.len());
+    assert!(proof.verify_within_peak(peaks[0], &vec![0u8], 1)?);
+
+    // a leaf being its own peak
+    let proof = mmr.proof_within_peak(11)?;
+
+    assert!(proof.path.is_empty());
+    assert!(proof.verify_within_peak(peaks[2], &vec![6u8], 11)?);
+
+    Ok(())
+}
+
+#[test]
+fn verify_within_peak_fails() -> Result<()> {
+    let mmr = make_mmr(7);
+    let peaks = mmr.peaks()?;
+    let proof = mmr.proof_within_peak(1)?;
+
+    assert!(matches!(
+        proof.verify_within_peak(peaks[1], &vec![0u8], 1),
+        Err(Error::InvalidRootHash(_, _))
+    ));
+    assert!(matches!(
+        proof.verify_within_peak(peaks[0], &vec![1u8], 1),
+        Err(Error::InvalidRootHash(_, _))
+    ));
+
+    // a full proof is not a proof up to the peak
+    let proof = mmr.proof(1)?;
+
+    assert_eq!(
+        Err(Error::InvalidProofLength),
+        proof.verify_within_peak(peaks[0], &vec![0u8], 1)
+    );
+
+    assert_eq!(Err(Error::ExpectingLeafNode(3)), mmr.proof_within_peak(3));
+
+    Ok(())
+}
+
+#[test]
+fn is_valid_shape_works() -> Result<()> {
+    let mmr = make_mmr(11);
+
+    for pos in [1u64, 2, 4, 5, 8, 9, 11, 12, 16, 17, 19].iter() {
+        assert!(mmr.proof(*pos)?.is_valid_shape(*pos));
+    }
+
+    // two lower peaks are bagged into a single hash
+    let proof = mmr.proof(1)?;
+
+    assert_eq!(4, proof.path.len());
+    assert!(proof.is_valid_shape(1));
+
+    // a proof for another position
+    assert!(!proof.is_valid_shape(19));
+
+    // not a leaf, out of range
+    assert!(!proof.is_valid_shape(3));
+    assert!(!proof.is_valid_shape(20));
+
+    Ok(())
+}
+
+#[test]
+fn is_valid_shape_fails() -> Result<()> {
+    let mmr = make_mmr(11);
+
+    let mut proof = mmr.proof(5)?;
+    proof.path.pop();
+
+    assert!(!proof.is_valid_shape(5));
+
+    let mut proof = mmr.proof(5)?;
+    proof.path.push(Hash::default());
+
+    assert!(!proof.is_valid_shape(5));
+
+    // an unstable MMR size
+    let mut proof = mmr.proof(5)?;
+    proof.mmr_size = 17;
+
+    assert!(!proof.is_valid_shape(5));
+
+    Ok(())
+}
+
+#[test]
+fn verify_binds_position() -> Result<()> {
+    // all leaves are the same element
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    for _ in 0..4 {
+        mmr.append(&vec![42u8])?;
+    }
+
+    let root = mmr.root()?;
+    let proof = mmr.proof(1)?;
+
+    assert!(proof.verify(root, &vec![42u8], 1)?);
+
+    for pos in [2u64, 4, 5].iter() {
+        assert!(matches!(
+            proof.verify(root, &vec![42u8], *pos),
+            Err(Error::InvalidRootHash(_, _))
+        ));
+    }
+
+    // a proof for the sibling does not verify for the leaf either
+    let proof = mmr.proof(2)?;
+
+    assert!(proof.verify(root, &vec![42u8], 2)?);
+    assert!(proof.verify(root, &vec![42u8], 1).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn verify_with_peak_index_works() -> Result<()> {
+    // peaks at positions 15, 18 and 19
+    let mmr = make_mmr(11);
+    let root = mmr.root()?;
+
+    let leaves = [
+        (1u64, 0u8, 0usize),
+        (12, 7, 0),
+        (16, 8, 1),
+        (17, 9, 1),
+        (19, 10, 2),
+    ];
+
+    for &(pos, leaf, peak) in leaves.iter() {
+        let proof = mmr.proof(pos)?;
+
+        assert_eq!(
+            (true, peak),
+            proof.verify_with_peak_index(root, &vec![leaf], pos)?
+        );
+    }
+
+    let proof = mmr.proof(16)?;
+
+    assert!(matches!(
+        proof.verify_with_peak_index(root, &vec![9u8], 16),
+        Err(Error::InvalidRootHash(_, _))
+    ));
+    assert_eq!(
+        Err(Error::PositionOutOfRange(20, 19)),
+        proof.verify_with_peak_index(root, &vec![9u8], 20)
+    );
+
+    Ok(())
+}
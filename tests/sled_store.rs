@@ -0,0 +1,101 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! MMR sled store tests
+
+#![cfg(feature = "sled")]
+
+use arber::{Error, Hash, MerkleMountainRange, Result, SledStore, Store, VecStore};
+
+type E = Vec<u8>;
+
+#[test]
+fn sled_store_persists() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("arber-sled-{}", std::process::id()));
+
+    let s = VecStore::<E>::new();
+    let mut want = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    let root = {
+        let db = sled::open(&path).unwrap();
+        let s = SledStore::<E>::open(&db)?;
+        let mut mmr = MerkleMountainRange::<E, SledStore<E>>::new(0, s);
+
+        for i in 0..100u8 {
+            let n = vec![i, 10];
+            mmr.append(&n)?;
+            want.append(&n)?;
+        }
+
+        db.flush().unwrap();
+        mmr.root()?
+    };
+
+    assert_eq!(want.root()?, root);
+
+    let db = sled::open(&path).unwrap();
+    let s = SledStore::<E>::open(&db)?;
+    let size = s.len();
+    let mmr = MerkleMountainRange::<E, SledStore<E>>::new(size, s);
+
+    assert_eq!(want.size(), size);
+    assert_eq!(root, mmr.root()?);
+    assert!(mmr.validate()?);
+    assert_eq!(100, mmr.leaf_data_all()?.len());
+    assert_eq!(vec![99u8, 10], mmr.leaf_data_all()?[99]);
+    assert!(mmr.proof(5)?.verify(root, &vec![3u8, 10], 5)?);
+
+    drop(db);
+    std::fs::remove_dir_all(&path).unwrap();
+
+    Ok(())
+}
+
+#[test]
+fn sled_store_invalid_hash_length() -> Result<()> {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let mut s = SledStore::<E>::open(&db)?;
+
+    s.append(&vec![0u8], &[Hash([1; 32])])?;
+    assert_eq!(Hash([1; 32]), s.hash_at(0)?);
+
+    let hashes = db.open_tree("hashes").unwrap();
+    hashes.insert(0u64.to_be_bytes(), &[1u8; 31][..]).unwrap();
+
+    assert_eq!(
+        Err(Error::StoreIo("invalid hash length".to_string())),
+        s.hash_at(0)
+    );
+    assert_eq!(Err(Error::MissingHashAtIndex(1)), s.hash_at(1));
+
+    Ok(())
+}
+
+#[test]
+fn sled_store_undo_append() -> Result<()> {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let mut s = SledStore::<E>::open(&db)?;
+
+    s.append(&vec![0u8], &[Hash([1; 32])])?;
+    s.append(&vec![1u8], &[Hash([2; 32]), Hash([3; 32])])?;
+    s.undo_append(2)?;
+
+    assert_eq!(1, s.len());
+    assert_eq!(vec![vec![0u8]], s.leaf_data()?);
+    assert_eq!(Err(Error::MissingHashAtIndex(1)), s.hash_at(1));
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate `pallet-mmr` compatibility tests
+
+use arber::{
+    compat::substrate::{verify_substrate_proof, SubstrateProof},
+    hash, Error, Hash, MerkleMountainRange, Result, VecStore,
+};
+
+// Test vectors generated with `ckb-merkle-mountain-range` 0.5.2, the MMR implementation
+// `pallet-mmr` is based on, for a MMR with 7 `u32` leaves `0..7`. Leaves are hashed as
+// `BlakeTwo256(leaf.encode())` and merged as `BlakeTwo256(left || right)`.
+const ROOT: Hash = hash!("0x2774d8b1525d45e9251fa9746c9205c69b5879988f740c09438864988c035a1b");
+
+const PROOF_0: [Hash; 3] = [
+    hash!("0xe12c22d4f162d9a012c9319233da5d3e923cc5e1029b8f90e47249c9ab256b35"),
+    hash!("0xea750bdb0a08f96991f00ceaf9c3517805b1844866091df48b3612a24225429a"),
+    hash!("0x552d6069cf608abededcc7399a82535ad050ece6e1ce5eb0b9810bb3dbff5af9"),
+];
+
+const PROOF_3: [Hash; 3] = [
+    hash!("0x7b0aa1735e5ba58d3236316c671fe4f00ed366ee72417c9ed02a53a8019e85b8"),
+    hash!("0xdd1445ec419376975790d7d4e487dfa5fca42a75f41e8893bc2d8b02c527f8f4"),
+    hash!("0x552d6069cf608abededcc7399a82535ad050ece6e1ce5eb0b9810bb3dbff5af9"),
+];
+
+const PROOF_4: [Hash; 3] = [
+    hash!("0xf0c1d8dd595c1e705ff3e42cb104b5b558b2fe09577b1ec44c0e0ea67982a884"),
+    hash!("0x8c35d22f459d77ca4c0b0b5035869766d60d182b9716ab3e8879e066478899a8"),
+    hash!("0xf4aac2fbe33f03554bfeb559ea2690ed8521caa4be961e61c91ac9a1530dce7a"),
+];
+
+const PROOF_6: [Hash; 2] = [
+    hash!("0xf0c1d8dd595c1e705ff3e42cb104b5b558b2fe09577b1ec44c0e0ea67982a884"),
+    hash!("0x86e27cc779fc3f19c1cf4ece5f9ae8a2b7cc24301e2cd17fad15342e495c187d"),
+];
+
+// Root of a MMR with the single `u32` leaf `42`, generated like the vectors above.
+const SINGLE_ROOT: Hash =
+    hash!("0xde037c7466270a63a0f6cc9a3b5ad1a3067a64a0b7e626f4ea9e95cd6533ed89");
+
+fn proof(items: &[Hash]) -> SubstrateProof {
+    SubstrateProof {
+        leaf_count: 7,
+        items: items.to_vec(),
+    }
+}
+
+#[test]
+fn verify_substrate_proof_works() -> Result<()> {
+    // leaf within the leftmost peak, peaks to the right are bagged
+    assert!(verify_substrate_proof(ROOT, &0u32, 0, &proof(&PROOF_0))?);
+    assert!(verify_substrate_proof(ROOT, &3u32, 3, &proof(&PROOF_3))?);
+
+    // left peak first, a single peak to the right is not bagged
+    assert!(verify_substrate_proof(ROOT, &4u32, 4, &proof(&PROOF_4))?);
+
+    // leaf is a peak itself
+    assert!(verify_substrate_proof(ROOT, &6u32, 6, &proof(&PROOF_6))?);
+
+    Ok(())
+}
+
+#[test]
+fn verify_substrate_proof_single_leaf() -> Result<()> {
+    let p = SubstrateProof {
+        leaf_count: 1,
+        items: vec![],
+    };
+
+    assert!(verify_substrate_proof(SINGLE_ROOT, &42u32, 0, &p)?);
+
+    Ok(())
+}
+
+#[test]
+fn verify_substrate_proof_fails() {
+    let root_mismatch =
+        |res: Result<bool>| matches!(res, Err(Error::InvalidRootHash(_, want)) if want == ROOT);
+
+    // wrong leaf
+    assert!(root_mismatch(verify_substrate_proof(
+        ROOT,
+        &1u32,
+        0,
+        &proof(&PROOF_0)
+    )));
+
+    // tampered sibling hash
+    let mut items = PROOF_0;
+    items[0].0[0] ^= 1;
+    assert!(root_mismatch(verify_substrate_proof(
+        ROOT,
+        &0u32,
+        0,
+        &proof(&items)
+    )));
+
+    // wrong leaf index
+    assert!(root_mismatch(verify_substrate_proof(
+        ROOT,
+        &1u32,
+        1,
+        &proof(&PROOF_0)
+    )));
+
+    // items out of order
+    let items = [PROOF_4[1], PROOF_4[0], PROOF_4[2]];
+    assert!(root_mismatch(verify_substrate_proof(
+        ROOT,
+        &4u32,
+        4,
+        &proof(&items)
+    )));
+}
+
+#[test]
+fn verify_substrate_proof_errors() {
+    assert_eq!(
+        Err(Error::LeafIndexOutOfRange(7, 7)),
+        verify_substrate_proof(ROOT, &7u32, 7, &proof(&PROOF_0))
+    );
+
+    assert_eq!(
+        Err(Error::InvalidProofLength),
+        verify_substrate_proof(ROOT, &0u32, 0, &proof(&PROOF_0[..2]))
+    );
+
+    let mut items = PROOF_0.to_vec();
+    items.push(PROOF_0[2]);
+
+    assert_eq!(
+        Err(Error::InvalidProofLength),
+        verify_substrate_proof(ROOT, &0u32, 0, &proof(&items))
+    );
+}
+
+#[test]
+fn arber_proof_not_interchangeable() -> Result<()> {
+    let s = VecStore::<u32>::new();
+    let mut mmr = MerkleMountainRange::<u32, VecStore<u32>>::new(0, s);
+
+    for i in 0..7u32 {
+        mmr.append(&i)?;
+    }
+
+    assert_ne!(ROOT, mmr.root()?);
+    assert!(verify_substrate_proof(ROOT, &0u32, 0, &proof(&mmr.proof(1)?.path)).is_err());
+
+    Ok(())
+}
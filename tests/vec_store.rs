@@ -16,7 +16,7 @@
 
 //! MMR vector store tests
 
-use arber::{MerkleMountainRange, Result, VecStore};
+use arber::{MerkleMountainRange, PackedVecStore, Result, VecStore};
 
 type E = Vec<u8>;
 
@@ -123,3 +123,27 @@ fn peaks() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn packed_vec_store_works() -> Result<()> {
+    let s = VecStore::<E>::new();
+    let mut mmr = MerkleMountainRange::<E, VecStore<E>>::new(0, s);
+
+    let s = PackedVecStore::<E>::new();
+    let mut packed = MerkleMountainRange::<E, PackedVecStore<E>>::new(0, s);
+
+    for i in 0..=10u8 {
+        let n = vec![i, 10];
+        assert_eq!(mmr.append(&n)?, packed.append(&n)?);
+    }
+
+    assert!(packed.validate()?);
+    assert_eq!(mmr.root()?, packed.root()?);
+    assert_eq!(mmr.peaks()?, packed.peaks()?);
+
+    for pos in [1, 2, 4, 5, 8, 9, 11, 12, 16, 17, 19].iter() {
+        assert_eq!(mmr.proof(*pos)?, packed.proof(*pos)?);
+    }
+
+    Ok(())
+}